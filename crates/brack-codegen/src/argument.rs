@@ -0,0 +1,496 @@
+use anyhow::Result;
+use brack_plugin::{
+    plugins::Plugins,
+    references::References,
+    types::{arg_counter, Type},
+    value::Value,
+};
+use brack_tokenizer::{diagnostic::Diagnostic, tokens::Location};
+use brack_transformer::{ast::AST, command::Arg, unparse::unparse};
+
+use crate::{block, curly, expr, raw, square, text};
+
+#[derive(Debug)]
+pub(crate) struct Argument {
+    pub name: Option<String>,
+    pub value: String,
+    /// The argument as written, which is the label for a parameter of `TReference`.
+    pub source: String,
+    /// The info string when the argument is a raw block.
+    pub info: Option<String>,
+    /// Whether the argument is the body of a block command.
+    pub body: bool,
+    pub location: Location,
+}
+
+pub(crate) fn generate(arg: &Arg, plugins: &mut Plugins) -> Result<Argument> {
+    let ast = &arg.value;
+    // Raw blocks are passed to the command untouched, without hooks.
+    if let Some(raw) = raw::as_argument(ast) {
+        return Ok(Argument {
+            name: arg.name.clone(),
+            value: raw.raw.content.clone(),
+            source: unparse(ast).trim().to_string(),
+            info: raw.raw.info.clone(),
+            body: arg.is_body(),
+            location: arg.location.clone(),
+        });
+    }
+    let value = match ast {
+        AST::Expr(_) => expr::generate(ast, plugins)?,
+        AST::Curly(_) => curly::generate(ast, plugins)?,
+        AST::Square(_) => square::generate(ast, plugins)?,
+        AST::Text(_) => text::generate(ast, plugins)?,
+        AST::Block(_) => block::generate(ast, plugins)?,
+        AST::Angle(_) => anyhow::bail!("Angle must be expanded by the macro expander."),
+        ast => anyhow::bail!(
+            "Command cannot take the following node as an argument\n{}",
+            ast
+        ),
+    };
+    Ok(Argument {
+        name: arg.name.clone(),
+        value,
+        source: unparse(ast).trim().to_string(),
+        info: None,
+        body: arg.is_body(),
+        location: arg.location.clone(),
+    })
+}
+
+/// Binds arguments to the parameters of a command.
+/// Positional arguments fill the parameters in order,
+/// and named arguments are bound to the parameters with the same name.
+/// An argument named after no parameter is a positional argument whose text is `name=value`,
+/// so an `=` in a text does not have to be escaped.
+/// Parameters of `TMetadata` and `TNumber` are not given by the caller
+/// but bound to `metadata` and the number of the call `id` in `references`.
+/// The label given to a parameter of `TReference` is resolved in `references`.
+/// The body of a block command is bound to the parameter of `TBlock` if there is one,
+/// and otherwise to the next positional parameter.
+/// `location` is the location of the command, used when an argument is missing.
+pub(crate) fn bind(
+    ident_name: &str,
+    arg_types: &[(String, Type)],
+    arguments: Vec<Argument>,
+    metadata: &serde_json::Value,
+    references: &References,
+    id: &str,
+    location: &Location,
+) -> Result<Vec<Value>> {
+    let (min, max) = arg_counter(&arg_types.iter().map(|(_, t)| t).cloned().collect());
+    let parameters = arg_types
+        .iter()
+        .filter(|(_, t)| !t.is_implicit())
+        .map(|(name, _)| format!("`{}`", name))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if arguments.len() < min {
+        anyhow::bail!(Diagnostic::error(
+            "B0021",
+            format!("{} requires at least {} arguments", ident_name, min),
+            location.clone(),
+        )
+        .with_help(format!("the parameters are {}", parameters)));
+    }
+
+    let mut slots: Vec<Vec<Argument>> = arg_types.iter().map(|_| vec![]).collect();
+    let mut position = 0;
+    let mut first_named: Option<Location> = None;
+    for mut argument in arguments {
+        if let Some(name) = argument
+            .name
+            .clone()
+            .filter(|name| arg_types.iter().all(|(parameter, _)| parameter != name))
+        {
+            argument.name = None;
+            argument.value = format!("{}={}", name, argument.value);
+            argument.source = format!("{}={}", name, argument.source);
+        }
+        let body_index = arg_types
+            .iter()
+            .position(|(_, t)| *t == Type::TBlock)
+            .filter(|_| argument.body);
+        let index = match (&argument.name, body_index) {
+            (None, Some(index)) => {
+                if let Some(previous) = slots[index].first() {
+                    anyhow::bail!(Diagnostic::error(
+                        "B0023",
+                        format!(
+                            "`{}` of {} is given more than once",
+                            arg_types[index].0, ident_name
+                        ),
+                        argument.location.clone(),
+                    )
+                    .with_label(previous.location.clone(), "first given here")
+                    .with_help("the body is bound to the parameter of a block"));
+                }
+                index
+            }
+            (Some(name), _) => {
+                let index = arg_types
+                    .iter()
+                    .position(|(parameter, t)| parameter == name && !t.is_implicit())
+                    .ok_or_else(|| {
+                        Diagnostic::error(
+                            "B0022",
+                            format!("{} has no parameter named `{}`", ident_name, name),
+                            argument.location.clone(),
+                        )
+                        .with_help(format!("the parameters are {}", parameters))
+                    })?;
+                if let Some(previous) = slots[index].first() {
+                    anyhow::bail!(Diagnostic::error(
+                        "B0023",
+                        format!("`{}` of {} is given more than once", name, ident_name),
+                        argument.location.clone(),
+                    )
+                    .with_label(previous.location.clone(), "first given here"));
+                }
+                first_named.get_or_insert_with(|| argument.location.clone());
+                index
+            }
+            (None, None) => {
+                if let Some(named) = &first_named {
+                    anyhow::bail!(Diagnostic::error(
+                        "B0024",
+                        format!(
+                            "{} takes positional arguments before named arguments",
+                            ident_name
+                        ),
+                        argument.location.clone(),
+                    )
+                    .with_label(named.clone(), "named argument given here"));
+                }
+                while arg_types
+                    .get(position)
+                    .is_some_and(|(_, t)| t.is_implicit())
+                {
+                    position += 1;
+                }
+                if position >= arg_types.len() {
+                    anyhow::bail!(Diagnostic::error(
+                        "B0021",
+                        format!("{} requires at most {} arguments", ident_name, max),
+                        argument.location.clone(),
+                    )
+                    .with_help(format!("the parameters are {}", parameters)));
+                }
+                let index = position;
+                // An array takes all the remaining positional arguments.
+                if !matches!(arg_types[index].1, Type::TArray(_)) {
+                    position += 1;
+                }
+                index
+            }
+        };
+        slots[index].push(argument);
+    }
+
+    let mut args = vec![];
+    for ((name, t), mut slot) in arg_types.iter().zip(slots) {
+        let arg = match t {
+            Type::TMetadata => Value::Metadata(metadata.clone()),
+            Type::TNumber => Value::Number(references.number(id).ok_or_else(|| {
+                anyhow::anyhow!("{} takes a number, but has no counter", ident_name)
+            })?),
+            Type::TOption(_) => Value::TextOption(slot.pop().map(|argument| argument.value)),
+            Type::TArray(_) => {
+                Value::TextArray(slot.into_iter().map(|argument| argument.value).collect())
+            }
+            t => {
+                let argument = slot.pop().ok_or_else(|| {
+                    Diagnostic::error(
+                        "B0025",
+                        format!("{} requires the argument `{}`", ident_name, name),
+                        location.clone(),
+                    )
+                    .with_help(format!("pass it by name as `{}=...`", name))
+                })?;
+                match t {
+                    Type::TRaw => Value::Raw(argument.info, argument.value),
+                    Type::TReference => {
+                        Value::Reference(references.resolve(&argument.source, &argument.location)?)
+                    }
+                    _ => Value::Text(argument.value),
+                }
+            }
+        };
+        args.push(arg);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_plugin::{
+        references::{References, Target},
+        types::Type,
+        value::Value,
+    };
+    use brack_tokenizer::{diagnostic::Diagnostic, tokens::mock_location};
+
+    use super::{bind, Argument};
+
+    fn positional(value: &str) -> Argument {
+        Argument {
+            name: None,
+            value: value.to_string(),
+            source: value.to_string(),
+            info: None,
+            body: false,
+            location: mock_location(),
+        }
+    }
+
+    fn named(name: &str, value: &str) -> Argument {
+        Argument {
+            name: Some(name.to_string()),
+            value: value.to_string(),
+            source: value.to_string(),
+            info: None,
+            body: false,
+            location: mock_location(),
+        }
+    }
+
+    fn anchor() -> Vec<(String, Type)> {
+        vec![
+            ("text".to_string(), Type::TInline),
+            ("url".to_string(), Type::TInline),
+            ("rel".to_string(), Type::TOption(Box::new(Type::TInline))),
+            ("title".to_string(), Type::TOption(Box::new(Type::TInline))),
+        ]
+    }
+
+    #[test]
+    fn test_bind_named_argument_skips_optional_parameters() -> Result<()> {
+        let args = bind(
+            "anchor",
+            &anchor(),
+            vec![positional("Brack"), positional("url"), named("title", "t")],
+            &serde_json::Value::Null,
+            &References::default(),
+            "",
+            &mock_location(),
+        )?;
+        assert_eq!(
+            args,
+            vec![
+                Value::Text("Brack".to_string()),
+                Value::Text("url".to_string()),
+                Value::TextOption(None),
+                Value::TextOption(Some("t".to_string())),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bind_required_parameters_by_name() -> Result<()> {
+        let args = bind(
+            "anchor",
+            &anchor(),
+            vec![named("url", "url"), named("text", "Brack")],
+            &serde_json::Value::Null,
+            &References::default(),
+            "",
+            &mock_location(),
+        )?;
+        assert_eq!(args[0], Value::Text("Brack".to_string()));
+        assert_eq!(args[1], Value::Text("url".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bind_array_takes_remaining_positional_arguments() -> Result<()> {
+        let arg_types = vec![
+            ("items".to_string(), Type::TArray(Box::new(Type::TInline))),
+            ("sep".to_string(), Type::TOption(Box::new(Type::TInline))),
+        ];
+        let args = bind(
+            "list",
+            &arg_types,
+            vec![positional("a"), positional("b"), named("sep", ",")],
+            &serde_json::Value::Null,
+            &References::default(),
+            "",
+            &mock_location(),
+        )?;
+        assert_eq!(
+            args,
+            vec![
+                Value::TextArray(vec!["a".to_string(), "b".to_string()]),
+                Value::TextOption(Some(",".to_string())),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bind_body_to_block_parameter() -> Result<()> {
+        let arg_types = vec![
+            ("kind".to_string(), Type::TInline),
+            ("body".to_string(), Type::TBlock),
+            ("title".to_string(), Type::TOption(Box::new(Type::TInline))),
+        ];
+        let body = Argument {
+            body: true,
+            ..positional("<p>Hello</p>")
+        };
+        let args = bind(
+            "callout",
+            &arg_types,
+            vec![positional("note"), named("title", "t"), body],
+            &serde_json::Value::Null,
+            &References::default(),
+            "",
+            &mock_location(),
+        )?;
+        assert_eq!(
+            args,
+            vec![
+                Value::Text("note".to_string()),
+                Value::Text("<p>Hello</p>".to_string()),
+                Value::TextOption(Some("t".to_string())),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bind_fills_metadata_parameter() -> Result<()> {
+        let arg_types = vec![
+            ("text".to_string(), Type::TInline),
+            ("metadata".to_string(), Type::TMetadata),
+        ];
+        let metadata = serde_json::json!({ "title": "Brack" });
+        let args = bind(
+            "title",
+            &arg_types,
+            vec![positional("a")],
+            &metadata,
+            &References::default(),
+            "",
+            &mock_location(),
+        )?;
+        assert_eq!(
+            args,
+            vec![Value::Text("a".to_string()), Value::Metadata(metadata)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bind_references_and_numbers() -> Result<()> {
+        let mut references = References::default();
+        let number = references.count("figure", "fig");
+        let target = Target {
+            label: "overview".to_string(),
+            id: "fig".to_string(),
+            counter: Some("figure".to_string()),
+            number: Some(number),
+        };
+        references.define(target.clone(), mock_location())?;
+        let arg_types = vec![
+            ("number".to_string(), Type::TNumber),
+            ("see".to_string(), Type::TReference),
+        ];
+        let args = bind(
+            "figure",
+            &arg_types,
+            vec![positional("overview")],
+            &serde_json::Value::Null,
+            &references,
+            "fig",
+            &mock_location(),
+        )?;
+        assert_eq!(args, vec![Value::Number(1), Value::Reference(target)]);
+        let error = bind(
+            "figure",
+            &arg_types,
+            vec![positional("summary")],
+            &serde_json::Value::Null,
+            &references,
+            "fig",
+            &mock_location(),
+        )
+        .unwrap_err();
+        let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+        assert_eq!(diagnostic.code, "B0041");
+        Ok(())
+    }
+
+    #[test]
+    fn test_bind_undeclared_names_as_text() -> Result<()> {
+        let args = bind(
+            "anchor",
+            &anchor(),
+            vec![positional("x=1"), named("href", "b"), named("title", "t")],
+            &serde_json::Value::Null,
+            &References::default(),
+            "",
+            &mock_location(),
+        )?;
+        assert_eq!(args[0], Value::Text("x=1".to_string()));
+        assert_eq!(args[1], Value::Text("href=b".to_string()));
+        let arg_types = vec![
+            ("text".to_string(), Type::TInline),
+            ("metadata".to_string(), Type::TMetadata),
+        ];
+        let error = bind(
+            "title",
+            &arg_types,
+            vec![positional("a"), named("metadata", "b")],
+            &serde_json::Value::Null,
+            &References::default(),
+            "",
+            &mock_location(),
+        )
+        .unwrap_err();
+        let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+        assert_eq!(diagnostic.code, "B0022");
+        Ok(())
+    }
+
+    #[test]
+    fn test_bind_failures() {
+        let cases = vec![
+            (
+                vec![positional("a"), positional("b"), named("url", "c")],
+                "B0023",
+            ),
+            (
+                vec![named("title", "t"), positional("a"), positional("b")],
+                "B0024",
+            ),
+            (vec![positional("a"), named("title", "t")], "B0025"),
+            (
+                vec![
+                    positional("a"),
+                    positional("b"),
+                    positional("c"),
+                    positional("d"),
+                    positional("e"),
+                ],
+                "B0021",
+            ),
+        ];
+        for (arguments, code) in cases {
+            let error = bind(
+                "anchor",
+                &anchor(),
+                arguments,
+                &serde_json::Value::Null,
+                &References::default(),
+                "",
+                &mock_location(),
+            )
+            .unwrap_err();
+            let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+            assert_eq!(diagnostic.code, code);
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use brack_plugin::plugins::Plugins;
+use brack_transformer::ast::AST;
+
+use crate::stmt;
+
+/// Generates the statements in the body of a block command, which is passed to it as one argument.
+pub(crate) fn generate(ast: &AST, plugins: &mut Plugins) -> Result<String> {
+    match ast {
+        AST::Block(_) => (),
+        _ => anyhow::bail!("Block must be a block"),
+    };
+    let mut result = String::from("");
+    for child in ast.try_children()? {
+        let res = match child {
+            AST::Stmt(_) => stmt::generate(child, plugins)?,
+            ast => anyhow::bail!("Block cannot contain the following node\n{}", ast),
+        };
+        result.push_str(&res);
+    }
+    Ok(result)
+}
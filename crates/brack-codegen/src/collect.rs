@@ -0,0 +1,136 @@
+use anyhow::Result;
+use brack_plugin::{
+    metadata::Metadata,
+    references::{References, Target},
+    types::Type,
+};
+use brack_transformer::{
+    ast::AST,
+    command::{collect_commands, Arg, CommandCall},
+    unparse::unparse,
+};
+
+/// Numbers the calls of commands with a counter and registers the labels they define,
+/// in document order, before any command is called.
+/// `metadata_of` returns the metadata of the command a call refers to.
+pub(crate) fn collect<F>(ast: &AST, metadata_of: F) -> Result<References>
+where
+    F: Fn(&CommandCall) -> Option<Metadata>,
+{
+    let mut references = References::default();
+    for command in collect_commands(ast)? {
+        let Some(metadata) = metadata_of(&command) else {
+            continue;
+        };
+        let number = metadata
+            .counter
+            .as_ref()
+            .map(|counter| references.count(counter, &command.id));
+        let label = metadata
+            .label
+            .as_ref()
+            .and_then(|label| label_argument(&command, &metadata.argument_types, label));
+        if let Some(arg) = label {
+            let target = Target {
+                label: unparse(&arg.value).trim().to_string(),
+                id: command.id.clone(),
+                counter: metadata.counter.clone(),
+                number,
+            };
+            references.define(target, arg.location.clone())?;
+        }
+    }
+    Ok(references)
+}
+
+/// Finds the argument bound to the parameter `label`,
+/// given by name or at the position of the parameter among the ones the caller passes.
+fn label_argument<'a>(
+    command: &'a CommandCall,
+    arg_types: &[(String, Type)],
+    label: &str,
+) -> Option<&'a Arg> {
+    if let Some(arg) = command
+        .args
+        .iter()
+        .find(|arg| arg.name.as_deref() == Some(label))
+    {
+        return Some(arg);
+    }
+    let position = arg_types
+        .iter()
+        .filter(|(_, t)| !t.is_implicit())
+        .position(|(name, _)| name == label)?;
+    command
+        .args
+        .iter()
+        .filter(|arg| arg.name.is_none() && !arg.is_body())
+        .nth(position)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_plugin::{metadata::Metadata, types::Type};
+    use brack_tokenizer::diagnostic::Diagnostic;
+    use brack_transformer::{
+        command::{CommandCall, CommandKind},
+        test_util::transform_str,
+    };
+
+    use super::collect;
+
+    /// `{test.figure caption, label?}` is numbered by `figure` and `[test.ref label]` is not numbered.
+    fn metadata_of(command: &CommandCall) -> Option<Metadata> {
+        let (argument_types, counter, label) = match (command.kind, command.ident.as_str()) {
+            (CommandKind::Curly, "figure") => (
+                vec![
+                    ("caption".to_string(), Type::TInline),
+                    ("number".to_string(), Type::TNumber),
+                    ("label".to_string(), Type::TOption(Box::new(Type::TInline))),
+                ],
+                Some("figure".to_string()),
+                Some("label".to_string()),
+            ),
+            (CommandKind::Square, "ref") => {
+                (vec![("label".to_string(), Type::TReference)], None, None)
+            }
+            _ => return None,
+        };
+        Some(Metadata {
+            command_name: command.ident.clone(),
+            call_name: command.ident.clone(),
+            argument_types,
+            return_type: Type::TBlock,
+            counter,
+            label,
+        })
+    }
+
+    #[test]
+    fn test_collect_numbers_and_labels() -> Result<()> {
+        let ast = transform_str(
+            "See [test.ref b].\n\n{test.figure A, a}\n\n{test.figure B}\n\n{test.figure C, label=b}",
+        )?;
+        let references = collect(&ast, metadata_of)?;
+        let a = references.resolve("a", &ast.location())?;
+        let b = references.resolve("b", &ast.location())?;
+        assert_eq!((a.number, b.number), (Some(1), Some(3)));
+        assert_eq!(references.number(&b.id), Some(3));
+        assert_eq!(b.counter.as_deref(), Some("figure"));
+        let error = references.resolve("c", &ast.location()).unwrap_err();
+        let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+        assert_eq!(diagnostic.code, "B0041");
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_labels() -> Result<()> {
+        let ast = transform_str("{test.figure A, a}\n\n{test.figure B, a}")?;
+        let error = collect(&ast, metadata_of).unwrap_err();
+        let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+        assert_eq!(diagnostic.code, "B0040");
+        assert_eq!(diagnostic.secondary.len(), 1);
+        Ok(())
+    }
+}
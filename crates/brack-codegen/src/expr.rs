@@ -2,7 +2,7 @@ use anyhow::Result;
 use brack_plugin::{plugins::Plugins, value::Value};
 use brack_transformer::ast::AST;
 
-use crate::{curly, square, text};
+use crate::{curly, raw, square, text};
 
 pub(crate) fn generate(ast: &AST, plugins: &mut Plugins) -> Result<String> {
     match ast {
@@ -10,11 +10,12 @@ pub(crate) fn generate(ast: &AST, plugins: &mut Plugins) -> Result<String> {
         _ => anyhow::bail!("Expr must be an expr"),
     };
     let mut result = String::from("");
-    for child in ast.children() {
+    for child in ast.try_children()? {
         let res = match child {
             AST::Curly(_) => curly::generate(child, plugins)?,
             AST::Square(_) => square::generate(child, plugins)?,
             AST::Text(_) => text::generate(child, plugins)?,
+            AST::Raw(_) => raw::generate(child)?,
             AST::Angle(_) => anyhow::bail!("Angle must be expanded by the macro expander."),
             AST::Expr(_) => generate(child, plugins)?,
             ast => anyhow::bail!("Expr cannot contain the following node\n{}", ast),
@@ -1,22 +1,41 @@
 use anyhow::Result;
-use brack_plugin::{plugins::Plugins, value::Value};
-use brack_transformer::ast::AST;
+use brack_plugin::{plugins::Plugins, types::Type, value::Value};
+use brack_transformer::{
+    ast::AST,
+    command::{CommandCall, CommandKind},
+};
 
-use crate::{curly, expr, square, stmt, text};
+use crate::{collect, curly, expr, raw, square, stmt, text};
 
+/// Generates a document in two passes.
+/// The labels and numbers of the whole document are collected first,
+/// so that a command can refer to a label defined after it.
 pub fn generate(ast: &AST, plugins: &mut Plugins) -> Result<String> {
     match ast {
         AST::Document(_) => (),
         _ => anyhow::bail!("Document must be a document"),
     };
+    let references = collect::collect(ast, |command: &CommandCall| {
+        let typ = match command.kind {
+            CommandKind::Square => Type::TInline,
+            CommandKind::Curly => Type::TBlock,
+            CommandKind::Angle => Type::TAST,
+        };
+        plugins
+            .command_metadata(&command.module, &command.ident, typ)
+            .ok()
+            .cloned()
+    })?;
+    plugins.set_references(references);
     let mut result = String::from("");
-    for child in ast.children() {
+    for child in ast.try_children()? {
         let res = match child {
             AST::Stmt(_) => stmt::generate(child, plugins)?,
             AST::Expr(_) => expr::generate(child, plugins)?,
             AST::Curly(_) => curly::generate(child, plugins)?,
             AST::Square(_) => square::generate(child, plugins)?,
             AST::Text(_) => text::generate(child, plugins)?,
+            AST::Raw(_) => raw::generate(child)?,
             AST::Angle(_) => anyhow::bail!("Angle must be expanded by the macro expander."),
             ast => anyhow::bail!("Document cannot contain the following node\n{}", ast),
         };
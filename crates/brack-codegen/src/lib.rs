@@ -1,6 +1,10 @@
+mod argument;
+mod block;
+mod collect;
 mod curly;
 mod expr;
 pub mod generate;
+mod raw;
 mod square;
 mod stmt;
 mod text;
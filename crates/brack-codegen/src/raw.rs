@@ -0,0 +1,22 @@
+use anyhow::Result;
+use brack_transformer::ast::{RawNode, AST};
+
+pub(crate) fn generate(ast: &AST) -> Result<String> {
+    match ast {
+        AST::Raw(node) => Ok(node.raw.content.clone()),
+        _ => anyhow::bail!("Raw must be a raw block"),
+    }
+}
+
+/// Returns the raw block if the argument consists only of it.
+/// A body consisting only of a raw block is also passed as the raw block.
+pub(crate) fn as_argument(ast: &AST) -> Option<&RawNode> {
+    match ast {
+        AST::Raw(node) => Some(node),
+        AST::Expr(node) | AST::Stmt(node) | AST::Block(node) => match node.children.as_slice() {
+            [child] => as_argument(child),
+            _ => None,
+        },
+        _ => None,
+    }
+}
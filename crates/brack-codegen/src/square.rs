@@ -1,92 +1,39 @@
 use anyhow::Result;
-use brack_plugin::{
-    plugins::Plugins,
-    types::{arg_counter, Type},
-    value::Value,
+use brack_plugin::{plugins::Plugins, types::Type};
+use brack_tokenizer::diagnostic::Diagnostic;
+use brack_transformer::{
+    ast::AST,
+    command::{CommandCall, CommandKind},
 };
-use brack_transformer::ast::AST;
 
-use crate::{curly, expr, text};
+use crate::argument;
 
 pub(crate) fn generate(ast: &AST, plugins: &mut Plugins) -> Result<String> {
-    match ast {
-        AST::Square(_) => (),
-        _ => anyhow::bail!("Square must be a square"),
-    };
-    let mut arguments = vec![];
-    let module = ast
-        .children()
-        .first()
-        .ok_or_else(|| anyhow::anyhow!("Square must contain module and identifier"))?;
-    let ident = ast
-        .children()
-        .get(1)
-        .ok_or_else(|| anyhow::anyhow!("Square must contain module and identifier"))?;
-    for child in ast.children().iter().skip(2) {
-        let res = match child {
-            AST::Expr(_) => expr::generate(child, plugins)?,
-            AST::Curly(_) => curly::generate(child, plugins)?,
-            AST::Square(_) => generate(child, plugins)?,
-            AST::Text(_) => text::generate(child, plugins)?,
-            AST::Angle(_) => anyhow::bail!("Angle must be expanded by the macro expander."),
-            ast => anyhow::bail!("Square cannot contain the following node\n{}", ast),
-        };
-        arguments.push(res);
-    }
-
-    let module_name = match module {
-        AST::Module(module) => module.value.clone(),
-        _ => anyhow::bail!("Module must be a module"),
-    };
-    let module_name = match module_name {
-        Some(module_name) => module_name,
-        None => anyhow::bail!("Module name must be a string"),
-    };
-
-    let ident_name = match ident {
-        AST::Ident(ident) => ident.value.clone(),
-        _ => anyhow::bail!("Identifier must be an identifier"),
-    };
-    let ident_name = match ident_name {
-        Some(ident_name) => ident_name,
-        None => anyhow::bail!("Identifier name must be a string"),
-    };
-
-    let arg_types = plugins.argument_types(&module_name, &ident_name, Type::TInline)?;
-
-    let (min, max) = arg_counter(
-        &arg_types
-            .iter()
-            .map(|(_, t)| t)
-            .cloned()
-            .collect::<Vec<_>>(),
-    );
-
-    if arguments.len() < min {
-        // TODO: show the signature of the command
-        anyhow::bail!("{} requires at least {} arguments", ident_name, min);
-    }
-    if arguments.len() > max {
-        // TODO: show the signature of the command
-        anyhow::bail!("{} requires at most {} arguments", ident_name, max);
+    let command = CommandCall::resolve(ast)?;
+    if command.kind != CommandKind::Square {
+        anyhow::bail!("Square must be a square");
     }
-
-    let mut args = vec![];
-    for (i, (_, t)) in arg_types.iter().enumerate() {
-        let arg = match t {
-            Type::TOption(_) => {
-                if i < arguments.len() {
-                    Value::TextOption(Some(arguments[i].clone()))
-                } else {
-                    Value::TextOption(None)
-                }
-            }
-            Type::TArray(_) => Value::TextArray(arguments[i..].to_vec()),
-            _ => Value::Text(arguments[i].clone()),
-        };
-        args.push(arg);
+    let mut arguments = vec![];
+    for arg in &command.args {
+        arguments.push(argument::generate(arg, plugins)?);
     }
 
-    let result = plugins.call_inline_command(&module_name, &ident_name, args)?;
+    let arg_types = plugins
+        .argument_types(&command.module, &command.ident, Type::TInline)
+        .map_err(|error| Diagnostic::from_error(&error, "B0020", command.location.clone()))?;
+
+    let args = argument::bind(
+        &command.ident,
+        &arg_types,
+        arguments,
+        plugins.metadata(),
+        plugins.references(),
+        &command.id,
+        &command.location,
+    )?;
+
+    let result = plugins
+        .call_inline_command(&command.module, &command.ident, args)
+        .map_err(|error| Diagnostic::from_error(&error, "B0030", command.location.clone()))?;
     Ok(result)
 }
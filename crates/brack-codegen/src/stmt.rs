@@ -2,7 +2,7 @@ use anyhow::Result;
 use brack_plugin::{plugins::Plugins, value::Value};
 use brack_transformer::ast::AST;
 
-use crate::{curly, expr, square, text};
+use crate::{curly, expr, raw, square, text};
 
 pub(crate) fn generate(ast: &AST, plugins: &mut Plugins) -> Result<String> {
     match ast {
@@ -10,12 +10,13 @@ pub(crate) fn generate(ast: &AST, plugins: &mut Plugins) -> Result<String> {
         _ => anyhow::bail!("Stmt must be a stmt"),
     };
     let mut result = String::from("");
-    for child in ast.children() {
+    for child in ast.try_children()? {
         let res = match child {
             AST::Expr(_) => expr::generate(child, plugins)?,
             AST::Curly(_) => curly::generate(child, plugins)?,
             AST::Square(_) => square::generate(child, plugins)?,
             AST::Text(_) => text::generate(child, plugins)?,
+            AST::Raw(_) => raw::generate(child)?,
             AST::Angle(_) => anyhow::bail!("Angle must be expanded by the macro expander."),
             ast => anyhow::bail!("Stmt cannot contain the following node\n{}", ast),
         };
@@ -1,58 +1,462 @@
+use std::{collections::HashMap, time::Instant};
+
 use anyhow::Result;
-use brack_plugin::plugins::Plugins;
-use brack_transformer::ast::AST;
-
-fn expand_angle(overall_ast: &AST, ast: &AST, plugins: &mut Plugins) -> Result<AST> {
-    let mut module_name = String::from("");
-    let mut ident_name = String::from("");
-
-    for child in ast.children() {
-        match child {
-            AST::Module(node) => {
-                module_name = node
-                    .clone()
-                    .value
-                    .ok_or_else(|| anyhow::anyhow!("No value found"))?
-            }
-            AST::Ident(node) => {
-                ident_name = node
-                    .clone()
-                    .value
-                    .ok_or_else(|| anyhow::anyhow!("No value found"))?
+use brack_plugin::{builtin, plugins::Plugins};
+use brack_tokenizer::{diagnostic::Diagnostic, tokens::Location};
+use brack_transformer::{
+    ast::{new_expr, InnerNode, AST},
+    command::{CommandCall, CommandKind},
+    fold::{walk_inner, Fold},
+    id::assign_ids_from,
+    unparse::unparse,
+};
+
+use crate::{
+    template::Template,
+    trace::{Invocation, InvocationKind, Trace},
+};
+
+/// The default limit of macro expansions nested in the results of other macros.
+pub const DEFAULT_MAX_DEPTH: usize = 32;
+
+/// Calls macros on behalf of the expander.
+/// `Plugins` calls the macros of WebAssembly plugins, and tests can use an in-process implementation.
+pub trait Macros {
+    /// Returns the subtree replacing `call`.
+    /// `document` is the document before the expansion and `depth` is the nesting of the call.
+    fn call_macro_command(
+        &mut self,
+        call: CommandCall,
+        document: &AST,
+        depth: usize,
+    ) -> Result<AST>;
+}
+
+impl Macros for Plugins {
+    fn call_macro_command(
+        &mut self,
+        call: CommandCall,
+        document: &AST,
+        depth: usize,
+    ) -> Result<AST> {
+        Plugins::call_macro_command(self, call, document, depth)
+    }
+}
+
+/// Expands all macros with the default depth limit.
+pub fn expander<M: Macros>(ast: &AST, macros: &mut M) -> Result<AST> {
+    expand(ast, macros, DEFAULT_MAX_DEPTH)
+}
+
+/// Replaces every angle node with the result of its macro,
+/// and every call of a template defined by `<brack.define ...>` with the template.
+/// Templates are defined in document order, so a template must be defined before it is called.
+/// The results are expanded again until no macro remains,
+/// and an error is returned if the expansions are nested deeper than `max_depth`
+/// or a macro expands to the same call again.
+pub fn expand<M: Macros>(ast: &AST, macros: &mut M, max_depth: usize) -> Result<AST> {
+    run(ast, macros, max_depth, None).0
+}
+
+/// Expands like `expand` and also records every invocation.
+/// The trace is returned even if the expansion fails, up to the failing call.
+pub fn expand_traced<M: Macros>(
+    ast: &AST,
+    macros: &mut M,
+    max_depth: usize,
+) -> (Result<AST>, Trace) {
+    let (result, trace) = run(ast, macros, max_depth, Some(Trace::default()));
+    (result, trace.unwrap_or_default())
+}
+
+fn run<M: Macros>(
+    ast: &AST,
+    macros: &mut M,
+    max_depth: usize,
+    trace: Option<Trace>,
+) -> (Result<AST>, Option<Trace>) {
+    let mut expander = Expander {
+        macros,
+        max_depth,
+        document: ast.clone(),
+        templates: HashMap::new(),
+        stack: vec![],
+        trace,
+        error: None,
+    };
+    let expanded = expander.fold_ast(ast.clone());
+    let result = match expander.error {
+        Some(error) => Err(error),
+        None => Ok(expanded),
+    };
+    (result, expander.trace)
+}
+
+struct Expander<'a, M: Macros> {
+    macros: &'a mut M,
+    max_depth: usize,
+    document: AST,
+    /// The templates defined so far, by their module and identifier.
+    templates: HashMap<(String, String), Template>,
+    /// The source and the location of the macro and template calls being expanded, outermost first.
+    stack: Vec<(String, Location)>,
+    trace: Option<Trace>,
+    error: Option<anyhow::Error>,
+}
+
+impl<M: Macros> Expander<'_, M> {
+    fn calls_template(&self, node: &InnerNode) -> bool {
+        match &node.children[..] {
+            [AST::Module(module), AST::Ident(ident), ..] => self.templates.contains_key(&(
+                module.value.clone().unwrap_or_default(),
+                ident.value.clone().unwrap_or_default(),
+            )),
+            _ => false,
+        }
+    }
+
+    fn define(&mut self, command: &CommandCall) -> Result<AST> {
+        let template = Template::define(command)?;
+        let key = (template.module.clone(), template.ident.clone());
+        if let Some(previous) = self.templates.get(&key) {
+            anyhow::bail!(Diagnostic::error(
+                "B0038",
+                format!("{} is defined more than once", template.name()),
+                command.location.clone(),
+            )
+            .with_label(previous.location.clone(), "first defined here"));
+        }
+        self.templates.insert(key, template);
+        Ok(new_expr(vec![], command.location.clone()))
+    }
+
+    fn expand_command(&mut self, ast: &AST) -> Result<AST> {
+        let command = CommandCall::resolve(ast)?;
+        if command.kind == CommandKind::Angle
+            && command.module == builtin::MODULE_NAME
+            && command.ident == "define"
+        {
+            return self.define(&command);
+        }
+        // Errors of nested expansions are reported at the call in the document.
+        let origin = self
+            .stack
+            .first()
+            .map_or(command.location.clone(), |(_, location)| location.clone());
+        let source = unparse(ast);
+        if self.stack.iter().any(|(call, _)| *call == source) {
+            anyhow::bail!(Diagnostic::error(
+                "B0037",
+                format!("{} expands to itself", source),
+                origin,
+            )
+            .with_help(format!(
+                "the expansions are {}",
+                self.stack
+                    .iter()
+                    .map(|(call, _)| call.as_str())
+                    .chain(std::iter::once(source.as_str()))
+                    .collect::<Vec<_>>()
+                    .join(" -> ")
+            )));
+        }
+        if self.stack.len() >= self.max_depth {
+            anyhow::bail!(Diagnostic::error(
+                "B0036",
+                format!(
+                    "Macro expansion is nested deeper than {} levels",
+                    self.max_depth
+                ),
+                origin,
+            )
+            .with_help(format!(
+                "the innermost macro is {}; raise `macro_depth` if the nesting is intended",
+                source
+            )));
+        }
+
+        let id = command.id.clone();
+        let location = command.location.clone();
+        let (module, ident) = (command.module.clone(), command.ident.clone());
+        let template = self.templates.get(&(module.clone(), ident.clone()));
+        let kind = match template {
+            Some(_) => InvocationKind::Template,
+            None => InvocationKind::Macro,
+        };
+        let start = Instant::now();
+        let result = match template {
+            Some(template) => template.instantiate(&command),
+            None => self
+                .macros
+                .call_macro_command(command, &self.document, self.stack.len())
+                .map_err(|error| Diagnostic::from_error(&error, "B0031", location.clone()).into()),
+        };
+        // The result takes the place of the call, so its nodes are given ids below the call.
+        let result = result.map(|mut expanded| {
+            assign_ids_from(&mut expanded, &id);
+            expanded
+        });
+        if let Some(trace) = &mut self.trace {
+            trace.invocations.push(Invocation {
+                kind,
+                module,
+                command: ident,
+                input: source.clone(),
+                location: location.clone(),
+                depth: self.stack.len(),
+                output: result.as_ref().ok().cloned(),
+                error: result.as_ref().err().map(|error| error.to_string()),
+                duration_us: start.elapsed().as_micros(),
+            });
+        }
+        let expanded = result?;
+
+        self.stack.push((source, location));
+        let expanded = self.fold_ast(expanded);
+        self.stack.pop();
+        Ok(expanded)
+    }
+
+    fn fold_command(&mut self, ast: AST) -> AST {
+        if self.error.is_some() {
+            return ast;
+        }
+        match self.expand_command(&ast) {
+            Ok(expanded) => expanded,
+            Err(error) => {
+                self.error = Some(error);
+                ast
             }
-            _ => (),
         }
     }
+}
 
-    let new_ast =
-        plugins.call_macro_command(&module_name, &ident_name, overall_ast.clone(), ast.id())?;
-    Ok(new_ast)
+/// Removes the statements left empty by definitions such as `<brack.define ...>` and `<brack.set ...>`,
+/// so that no empty paragraph is generated for them.
+fn remove_empty_stmts(mut node: InnerNode) -> InnerNode {
+    fn is_empty(ast: &AST) -> bool {
+        match ast {
+            AST::Stmt(node) | AST::Expr(node) => node.children.iter().all(is_empty),
+            _ => false,
+        }
+    }
+    node.children.retain(|child| !is_empty(child));
+    node
 }
 
-fn expand_other(overall_ast: &AST, ast: &AST, plugins: &mut Plugins) -> Result<AST> {
-    let mut children = vec![];
-    match ast {
-        AST::Text(_) => {
-            return Ok(ast.clone());
+impl<M: Macros> Fold for Expander<'_, M> {
+    fn fold_document(&mut self, node: InnerNode) -> AST {
+        AST::Document(remove_empty_stmts(walk_inner(self, node)))
+    }
+
+    fn fold_block(&mut self, node: InnerNode) -> AST {
+        AST::Block(remove_empty_stmts(walk_inner(self, node)))
+    }
+
+    fn fold_angle(&mut self, node: InnerNode) -> AST {
+        self.fold_command(AST::Angle(node))
+    }
+
+    fn fold_square(&mut self, node: InnerNode) -> AST {
+        if self.calls_template(&node) {
+            self.fold_command(AST::Square(node))
+        } else {
+            AST::Square(walk_inner(self, node))
         }
-        AST::Module(_) => {
-            return Ok(ast.clone());
+    }
+
+    fn fold_curly(&mut self, node: InnerNode) -> AST {
+        if self.calls_template(&node) {
+            self.fold_command(AST::Curly(node))
+        } else {
+            AST::Curly(walk_inner(self, node))
         }
-        AST::Ident(_) => {
-            return Ok(ast.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_tokenizer::{diagnostic::Diagnostic, source_map::SourceMap};
+    use brack_transformer::{
+        ast::{new_angle, new_expr, new_ident, new_module, new_text, AST},
+        command::CommandCall,
+        test_util::transform_str,
+        unparse::unparse,
+    };
+
+    use super::{expand, expand_traced, expander, Macros};
+    use crate::trace::InvocationKind;
+
+    /// `<test.upper text>` upper-cases its argument, `<test.twice arg>` repeats its argument,
+    /// `<test.depth>` is replaced with its depth, `<test.again>` expands to itself
+    /// and `<test.deeper ...>` expands to itself with one more argument.
+    struct Stub;
+
+    impl Macros for Stub {
+        fn call_macro_command(
+            &mut self,
+            call: CommandCall,
+            _document: &AST,
+            depth: usize,
+        ) -> Result<AST> {
+            let location = call.location.clone();
+            let args = call.args.into_iter().map(|arg| arg.value);
+            Ok(match call.ident.as_str() {
+                "upper" => new_text(
+                    Some(unparse(&new_expr(args.collect(), location.clone())).to_uppercase()),
+                    location,
+                ),
+                "twice" => new_expr(args.clone().chain(args).collect(), location),
+                "depth" => new_text(Some(depth.to_string()), location),
+                "again" | "deeper" => {
+                    let mut children = vec![
+                        new_module(Some("test".to_string()), location.clone()),
+                        new_ident(Some(call.ident.clone()), location.clone()),
+                    ];
+                    children.extend(args);
+                    if call.ident == "deeper" {
+                        children.push(new_expr(
+                            vec![new_text(Some("x".to_string()), location.clone())],
+                            location.clone(),
+                        ));
+                    }
+                    new_angle(children, location)
+                }
+                ident => anyhow::bail!("{} is not a macro", ident),
+            })
         }
-        _ => {}
     }
-    for child in ast.children() {
-        match child {
-            AST::Angle(_) => children.push(expand_angle(overall_ast, child, plugins)?),
-            _ => children.push(expand_other(overall_ast, child, plugins)?),
+
+    #[test]
+    fn test_expand_macros_in_stmts() -> Result<()> {
+        let ast = transform_str("Hello, <test.upper world>!\n\n[std.* <test.upper a>]")?;
+        let expanded = expander(&ast, &mut Stub)?;
+        assert_eq!(unparse(&expanded), "Hello, WORLD!\n\n[std.* A]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_results_of_macros_again() -> Result<()> {
+        let ast =
+            transform_str("<test.twice <test.upper a>><test.depth>, <test.twice <test.depth>>")?;
+        let expanded = expander(&ast, &mut Stub)?;
+        assert_eq!(unparse(&expanded), "AA0, 11");
+        Ok(())
+    }
+
+    #[test]
+    fn test_expansion_errors() -> Result<()> {
+        let cases = vec![("<test.again a>", "B0037"), ("<test.deeper>", "B0036")];
+        for (text, code) in cases {
+            let ast = transform_str(text)?;
+            let error = expand(&ast, &mut Stub, 8).unwrap_err();
+            let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+            assert_eq!(diagnostic.code, code, "{}", text);
         }
+        Ok(())
     }
-    Ok(ast.clone())
-}
 
-pub fn expander(ast: &AST, plugins: &mut Plugins) -> Result<AST> {
-    let overall_ast = ast.clone();
-    expand_other(&overall_ast, ast, plugins)
+    #[test]
+    fn test_expand_templates() -> Result<()> {
+        let ast = transform_str(
+            "<brack.define note, box, title, body, [test.b $title]: $body $$>\n\n{note.box Hi, <test.upper x>}\n\n[note.box body=$title, title=T]",
+        )?;
+        let expanded = expander(&ast, &mut Stub)?;
+        // The definition leaves no empty statement, and the spaces in the template are kept.
+        assert_eq!(expanded.try_children()?.len(), 2);
+        assert_eq!(
+            unparse(&expanded),
+            "[test.b Hi]: X $\n\n[test.b T]: $title $"
+        );
+        // `x=1` does not name a parameter, so it is a positional argument.
+        let ast = transform_str("<brack.define note, box, a, [test.b $a]>\n\n[note.box x=1]")?;
+        let expanded = expander(&ast, &mut Stub)?;
+        assert_eq!(unparse(&expanded), "[test.b x\\=1]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_errors() -> Result<()> {
+        let define = "<brack.define note, box, a, [test.b $a]>\n\n";
+        let cases = vec![
+            ("<brack.define note, box>".to_string(), "B0038"),
+            ("<brack.define note, box, a, a, $a>".to_string(), "B0038"),
+            ("<brack.define brack, box, $a>".to_string(), "B0038"),
+            (format!("{}{}", define, define), "B0038"),
+            (format!("{}[note.box]", define), "B0039"),
+            (format!("{}[note.box x, y]", define), "B0039"),
+            (format!("{}[note.box x, a=y]", define), "B0039"),
+            (
+                "<brack.define note, box, a, [note.box $a]>\n\n[note.box x]".to_string(),
+                "B0037",
+            ),
+        ];
+        for (text, code) in cases {
+            let ast = transform_str(&text)?;
+            let error = expander(&ast, &mut Stub).unwrap_err();
+            let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+            assert_eq!(diagnostic.code, code, "{}", text);
+            if code == "B0039" {
+                assert_eq!(diagnostic.secondary.len(), 1);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_trace_records_invocations() -> Result<()> {
+        let ast = transform_str("<brack.define t, w, a, <test.twice $a>>\n\n[t.w <test.upper a>]")?;
+        let (expanded, trace) = expand_traced(&ast, &mut Stub, 8);
+        assert_eq!(unparse(&expanded?), "AA");
+        let summary = trace
+            .invocations
+            .iter()
+            .map(|invocation| {
+                (
+                    invocation.kind,
+                    invocation.command.as_str(),
+                    invocation.depth,
+                    invocation.output.as_ref().map(unparse).unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    InvocationKind::Template,
+                    "w",
+                    0,
+                    "<test.twice <test.upper a>>".to_string()
+                ),
+                (
+                    InvocationKind::Macro,
+                    "twice",
+                    1,
+                    "<test.upper a><test.upper a>".to_string()
+                ),
+                (InvocationKind::Macro, "upper", 2, "A".to_string()),
+                (InvocationKind::Macro, "upper", 2, "A".to_string()),
+            ]
+        );
+        let text = trace.render(&SourceMap::new());
+        assert!(text.starts_with("#1 template t.w at <unknown>:3:1 ("));
+        assert!(text.contains("\n  #2 macro test.twice at <unknown>:1:"));
+
+        let ast = transform_str("<test.upper a>\n\n<test.missing b>")?;
+        let (expanded, trace) = expand_traced(&ast, &mut Stub, 8);
+        assert!(expanded.is_err());
+        let failed = &trace.invocations[1];
+        assert_eq!((failed.command.as_str(), failed.depth), ("missing", 0));
+        assert_eq!(failed.input, "<test.missing b>");
+        assert_eq!(failed.output, None);
+        assert_eq!(
+            failed.error.as_deref(),
+            Some("error[B0031]: missing is not a macro")
+        );
+        let text = trace.render(&SourceMap::new());
+        assert!(text.ends_with("  !! error[B0031]: missing is not a macro\n"));
+        Ok(())
+    }
 }
@@ -0,0 +1,290 @@
+use std::path::{Path, PathBuf};
+
+use brack_tokenizer::{
+    diagnostic::Diagnostic,
+    source_map::{FileId, SourceMap},
+    tokenize::tokenize_file,
+    tokens::Location,
+};
+use brack_transformer::{
+    ast::{new_stmt, InnerNode, AST},
+    command::{CommandCall, CommandKind},
+    visit::{walk_ast, Visit},
+};
+
+/// Replaces every line consisting of `<brack.include path>` with the statements of the file at `path`.
+/// The path is relative to the including file, and `.[]` is appended when it has no extension.
+/// Included files may include other files, and an include cycle is reported as an error.
+/// `file` is the file `ast` was read from.
+pub fn resolve_includes(
+    ast: AST,
+    file: FileId,
+    source_map: &mut SourceMap,
+) -> (AST, Vec<Diagnostic>) {
+    let mut includer = Includer {
+        source_map,
+        stack: vec![],
+        diagnostics: vec![],
+    };
+    if let Some(path) = includer
+        .source_map
+        .get(file)
+        .map(|source| source.path.clone())
+    {
+        includer.stack.push((canonical(&path), path));
+    }
+    let ast = includer.include_document(ast, file);
+    (ast, includer.diagnostics)
+}
+
+struct Includer<'a> {
+    source_map: &'a mut SourceMap,
+    /// The canonical and the displayed paths of the files being included, from the root document.
+    stack: Vec<(PathBuf, PathBuf)>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Includer<'_> {
+    fn include_document(&mut self, ast: AST, file: FileId) -> AST {
+        let AST::Document(node) = ast else {
+            return ast;
+        };
+        let mut children = vec![];
+        for stmt in node.children {
+            children.append(&mut self.include_stmt(stmt, file));
+        }
+        AST::Document(InnerNode { children, ..node })
+    }
+
+    /// Splits a statement at the include lines, which are replaced with the included statements.
+    fn include_stmt(&mut self, ast: AST, file: FileId) -> Vec<AST> {
+        let AST::Stmt(node) = ast else {
+            return vec![ast];
+        };
+        let mut stmts = vec![];
+        let mut lines = vec![];
+        for expr in node.children {
+            match as_include(&expr) {
+                Some(command) => {
+                    if !lines.is_empty() {
+                        stmts.push(new_stmt(std::mem::take(&mut lines), node.location.clone()));
+                    }
+                    stmts.append(&mut self.include(&command, file));
+                }
+                None => {
+                    self.check_misplaced(&expr);
+                    lines.push(expr);
+                }
+            }
+        }
+        if !lines.is_empty() {
+            stmts.push(new_stmt(lines, node.location.clone()));
+        }
+        stmts
+    }
+
+    fn include(&mut self, command: &CommandCall, file: FileId) -> Vec<AST> {
+        let Some(path) = self.resolve_path(command, file) else {
+            return vec![];
+        };
+        let canonical_path = canonical(&path);
+        if let Some(start) = self
+            .stack
+            .iter()
+            .position(|(canonical, _)| *canonical == canonical_path)
+        {
+            let cycle = self.stack[start..]
+                .iter()
+                .map(|(_, path)| path)
+                .chain(std::iter::once(&path))
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>();
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "B0033",
+                    format!("`{}` is included recursively", path.display()),
+                    command.location.clone(),
+                )
+                .with_help(format!("the include cycle is {}", cycle.join(" -> "))),
+            );
+            return vec![];
+        }
+
+        let included = match self.source_map.find(&path) {
+            Some(included) => included,
+            None => match self.source_map.load(&path) {
+                Ok(included) => included,
+                Err(error) => {
+                    self.diagnostics.push(Diagnostic::error(
+                        "B0032",
+                        format!("Cannot include `{}`: {}", path.display(), error),
+                        command.location.clone(),
+                    ));
+                    return vec![];
+                }
+            },
+        };
+        let tokens = match tokenize_file(self.source_map, included) {
+            Ok(tokens) => tokens,
+            Err(error) => {
+                self.diagnostics.push(Diagnostic::from_error(
+                    &error,
+                    "B0032",
+                    command.location.clone(),
+                ));
+                return vec![];
+            }
+        };
+        let (cst, errors) = brack_parser::parse::parse(&tokens);
+        self.diagnostics
+            .extend(errors.iter().map(|error| error.to_diagnostic()));
+        let (ast, errors) = brack_transformer::transform::transform(&cst);
+        self.diagnostics
+            .extend(errors.iter().map(|error| error.to_diagnostic()));
+
+        self.stack.push((canonical_path, path));
+        let ast = self.include_document(ast, included);
+        self.stack.pop();
+        match ast {
+            AST::Document(node) => node.children,
+            ast => vec![ast],
+        }
+    }
+
+    fn resolve_path(&mut self, command: &CommandCall, file: FileId) -> Option<PathBuf> {
+        let text = match &command.args[..] {
+            [arg] if arg.name.is_none() => text_of(&arg.value),
+            _ => None,
+        };
+        let Some(text) = text else {
+            self.diagnostics.push(
+                Diagnostic::error(
+                    "B0034",
+                    "brack.include takes exactly one path",
+                    command.location.clone(),
+                )
+                .with_help("write dots in the path as `\\.`, or omit the `.[]` extension"),
+            );
+            return None;
+        };
+        let mut path = self
+            .source_map
+            .get(file)
+            .and_then(|source| source.path.parent())
+            .unwrap_or(Path::new(""))
+            .join(text);
+        if path.extension().is_none() {
+            path.set_extension("[]");
+        }
+        Some(path)
+    }
+
+    fn check_misplaced(&mut self, ast: &AST) {
+        let mut finder = IncludeFinder { locations: vec![] };
+        finder.visit_ast(ast);
+        for location in finder.locations {
+            self.diagnostics.push(
+                Diagnostic::error("B0034", "brack.include must be on its own line", location)
+                    .with_help("move the include to a separate line"),
+            );
+        }
+    }
+}
+
+/// Returns the include command if `ast` is a line consisting only of it.
+fn as_include(ast: &AST) -> Option<CommandCall> {
+    let command = match ast {
+        AST::Expr(node) => match &node.children[..] {
+            [angle] => CommandCall::resolve(angle).ok()?,
+            _ => return None,
+        },
+        ast => CommandCall::resolve(ast).ok()?,
+    };
+    is_include(&command).then_some(command)
+}
+
+fn is_include(command: &CommandCall) -> bool {
+    command.kind == CommandKind::Angle && command.module == "brack" && command.ident == "include"
+}
+
+/// Concatenates the texts of an argument, or returns `None` if it contains commands.
+fn text_of(ast: &AST) -> Option<String> {
+    match ast {
+        AST::Text(leaf) => leaf.value.clone(),
+        AST::Expr(node) => node.children.iter().map(text_of).collect(),
+        _ => None,
+    }
+}
+
+/// Include cycles are detected by the canonical paths, and a path which cannot be canonicalized is kept as it is.
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+struct IncludeFinder {
+    locations: Vec<Location>,
+}
+
+impl Visit for IncludeFinder {
+    fn visit_ast(&mut self, ast: &AST) {
+        if let Ok(command) = CommandCall::resolve(ast) {
+            if is_include(&command) {
+                self.locations.push(command.location);
+            }
+        }
+        walk_ast(self, ast);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_tokenizer::{diagnostic::Diagnostic, source_map::SourceMap, tokenize::tokenize_file};
+    use brack_transformer::{ast::AST, unparse::unparse};
+
+    use super::resolve_includes;
+
+    fn include(path: &str) -> Result<(AST, Vec<Diagnostic>, SourceMap)> {
+        let mut source_map = SourceMap::new();
+        let file = source_map.load(std::env::current_dir()?.join(path))?;
+        let tokens = tokenize_file(&source_map, file)?;
+        let (cst, _) = brack_parser::parse::parse(&tokens);
+        let (ast, _) = brack_transformer::transform::transform(&cst);
+        let (ast, diagnostics) = resolve_includes(ast, file, &mut source_map);
+        Ok((ast, diagnostics, source_map))
+    }
+
+    #[test]
+    fn test_include_splices_statements() -> Result<()> {
+        let (ast, diagnostics, _) = include("test/include/main.[]")?;
+        assert!(diagnostics.is_empty());
+        assert_eq!(
+            unparse(&ast),
+            "{std.# Handbook}\n\nbefore\n\n{std.## Introduction}\n\nHello, [std.* World]!\n\nafter"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_include_keeps_locations_of_included_files() -> Result<()> {
+        let (_, diagnostics, source_map) = include("test/include/broken.[]")?;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "B0007");
+        let location = diagnostics[0].location().unwrap();
+        assert!(source_map
+            .render_location(&location)
+            .ends_with("chapters/unclosed.[]:2:1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_include_cycle() -> Result<()> {
+        let (_, diagnostics, _) = include("test/include/cycle_a.[]")?;
+        let codes = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.code.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(codes, vec!["B0033"]);
+        Ok(())
+    }
+}
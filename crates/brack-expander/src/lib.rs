@@ -1 +1,4 @@
 pub mod expand;
+pub mod include;
+mod template;
+pub mod trace;
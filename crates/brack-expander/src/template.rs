@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use brack_plugin::builtin;
+use brack_tokenizer::{diagnostic::Diagnostic, tokens::Location};
+use brack_transformer::{
+    ast::{new_expr, new_text, InnerNode, LeafNode, AST},
+    command::CommandCall,
+    fold::{walk_ast, Fold},
+    unparse::unparse,
+};
+
+/// A command defined in the document by `<brack.define module, ident, params..., template>`.
+/// Calling it replaces the call with the template,
+/// in which `$param` is substituted with the argument of the parameter and `$$` is a dollar sign.
+#[derive(Debug, Clone)]
+pub(crate) struct Template {
+    pub module: String,
+    pub ident: String,
+    params: Vec<String>,
+    body: AST,
+    pub location: Location,
+}
+
+impl Template {
+    pub(crate) fn define(call: &CommandCall) -> Result<Self> {
+        let invalid = |message: String| -> anyhow::Error {
+            Diagnostic::error("B0038", message, call.location.clone())
+                .with_help("write `<brack.define module, ident, params..., template>`")
+                .into()
+        };
+        let (body, names) = match call.args.split_last() {
+            Some((body, names)) if names.len() >= 2 => (body, names),
+            _ => {
+                return Err(invalid(
+                    "brack.define requires a module, an identifier and a template".to_string(),
+                ))
+            }
+        };
+        let mut names = names.iter().map(|arg| match &arg.name {
+            Some(name) => Err(invalid(format!(
+                "brack.define takes no named argument, but `{}` is given",
+                name
+            ))),
+            None => Ok(unparse(&arg.value).trim().to_string()),
+        });
+        let module = names.next().unwrap()?;
+        let ident = names.next().unwrap()?;
+        let params = names.collect::<Result<Vec<_>>>()?;
+        if module == builtin::MODULE_NAME {
+            return Err(invalid(format!(
+                "the module `{}` is reserved for the built-in commands",
+                module
+            )));
+        }
+        let is_word =
+            |name: &str| !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !is_word(&module) {
+            return Err(invalid(format!("`{}` is not a valid module name", module)));
+        }
+        if ident.is_empty() || ident.contains(char::is_whitespace) {
+            return Err(invalid(format!("`{}` is not a valid identifier", ident)));
+        }
+        for (index, param) in params.iter().enumerate() {
+            if !is_word(param) {
+                return Err(invalid(format!(
+                    "`{}` is not a valid parameter name",
+                    param
+                )));
+            }
+            if params[..index].contains(param) {
+                return Err(invalid(format!("the parameter `{}` is given twice", param)));
+            }
+        }
+        Ok(Self {
+            module,
+            ident,
+            params,
+            body: body.value.clone(),
+            location: call.location.clone(),
+        })
+    }
+
+    pub(crate) fn name(&self) -> String {
+        format!("{}.{}", self.module, self.ident)
+    }
+
+    /// Binds the arguments of `call` to the parameters in the same way as commands of plugins:
+    /// positional arguments come first in order, followed by named arguments.
+    /// Every parameter must be given exactly once.
+    pub(crate) fn instantiate(&self, call: &CommandCall) -> Result<AST> {
+        let mismatch = |message: String, location: Location| {
+            Diagnostic::error("B0039", message, location)
+                .with_label(self.location.clone(), "defined here")
+                .with_help(format!(
+                    "the parameters are {}",
+                    self.params
+                        .iter()
+                        .map(|param| format!("`{}`", param))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+        };
+        let mut values = HashMap::new();
+        let mut position = 0;
+        for arg in &call.args {
+            // An argument named after no parameter is a positional argument written as `name=value`.
+            let (name, value) = match &arg.name {
+                Some(name) if !self.params.contains(name) => {
+                    let mut children =
+                        vec![new_text(Some(format!("{}=", name)), arg.location.clone())];
+                    match &arg.value {
+                        AST::Expr(node) => children.extend(node.children.iter().cloned()),
+                        value => children.push(value.clone()),
+                    }
+                    (None, new_expr(children, arg.location.clone()))
+                }
+                name => (name.as_ref(), arg.value.clone()),
+            };
+            let param = match name {
+                Some(name) => self
+                    .params
+                    .iter()
+                    .find(|param| *param == name)
+                    .ok_or_else(|| {
+                        mismatch(
+                            format!("{} has no parameter named `{}`", self.name(), name),
+                            arg.location.clone(),
+                        )
+                    })?,
+                None => {
+                    position += 1;
+                    self.params.get(position - 1).ok_or_else(|| {
+                        mismatch(
+                            format!(
+                                "{} takes {} arguments, but {} are given",
+                                self.name(),
+                                self.params.len(),
+                                call.args.len()
+                            ),
+                            arg.location.clone(),
+                        )
+                    })?
+                }
+            };
+            if values.insert(param.clone(), value).is_some() {
+                anyhow::bail!(mismatch(
+                    format!("`{}` of {} is given more than once", param, self.name()),
+                    arg.location.clone(),
+                ));
+            }
+        }
+        if let Some(missing) = self
+            .params
+            .iter()
+            .find(|param| !values.contains_key(*param))
+        {
+            anyhow::bail!(mismatch(
+                format!("{} requires the argument `{}`", self.name(), missing),
+                call.location.clone(),
+            ));
+        }
+        Ok(Substitution { values: &values }.fold_ast(self.body.clone()))
+    }
+}
+
+/// Substitutes the placeholders in the template only.
+/// The arguments are spliced as they are, so a `$name` written in an argument is kept as text.
+struct Substitution<'a> {
+    values: &'a HashMap<String, AST>,
+}
+
+impl Substitution<'_> {
+    /// The nodes an argument is spliced as.
+    /// The body of a block command is spread into its expressions unless it fills a whole argument.
+    /// Children of the body that are not statements have nothing to spread and are skipped.
+    fn splice(value: &AST) -> Vec<AST> {
+        match value {
+            AST::Expr(node) => node.children.clone(),
+            AST::Block(node) => node
+                .children
+                .iter()
+                .filter_map(|stmt| stmt.try_children().ok())
+                .flatten()
+                .cloned()
+                .collect(),
+            value => vec![value.clone()],
+        }
+    }
+
+    fn substitute_text(&self, leaf: LeafNode) -> Vec<AST> {
+        let text = leaf.value.clone().unwrap_or_default();
+        let mut nodes = vec![];
+        let mut literal = String::new();
+        let mut rest = text.as_str();
+        while let Some(index) = rest.find('$') {
+            literal.push_str(&rest[..index]);
+            let after = &rest[index + 1..];
+            if let Some(after) = after.strip_prefix('$') {
+                literal.push('$');
+                rest = after;
+                continue;
+            }
+            let end = after
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            match self.values.get(&after[..end]) {
+                Some(value) => {
+                    if !literal.is_empty() {
+                        nodes.push(new_text(
+                            Some(std::mem::take(&mut literal)),
+                            leaf.location.clone(),
+                        ));
+                    }
+                    nodes.extend(Self::splice(value));
+                    rest = &after[end..];
+                }
+                None => {
+                    literal.push('$');
+                    rest = after;
+                }
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            nodes.push(new_text(Some(literal), leaf.location));
+        }
+        nodes
+    }
+}
+
+impl Fold for Substitution<'_> {
+    fn fold_expr(&mut self, node: InnerNode) -> AST {
+        // `{std.quote $body}` passes the body as the body of the inner command.
+        if let [AST::Text(leaf)] = &node.children[..] {
+            let placeholder = leaf.value.as_deref().unwrap_or_default().trim();
+            if let Some(block @ AST::Block(_)) = placeholder
+                .strip_prefix('$')
+                .and_then(|name| self.values.get(name))
+            {
+                return block.clone();
+            }
+        }
+        let mut children = vec![];
+        for child in node.children {
+            match child {
+                AST::Text(leaf) => children.extend(self.substitute_text(leaf)),
+                child => children.push(walk_ast(self, child)),
+            }
+        }
+        AST::Expr(InnerNode { children, ..node })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brack_tokenizer::tokens::mock_location;
+    use brack_transformer::{
+        ast::{new_block, new_expr, new_stmt, new_text},
+        unparse::unparse,
+    };
+
+    use super::Substitution;
+
+    #[test]
+    fn test_splice_skips_leaves_of_a_block() {
+        let text = |value: &str| new_text(Some(value.to_string()), mock_location());
+        let block = new_block(
+            vec![
+                new_stmt(
+                    vec![new_expr(vec![text("a")], mock_location())],
+                    mock_location(),
+                ),
+                text("b"),
+            ],
+            mock_location(),
+        );
+        let nodes = Substitution::splice(&block);
+        assert_eq!(nodes.iter().map(unparse).collect::<Vec<_>>(), vec!["a"]);
+    }
+}
@@ -0,0 +1,69 @@
+use brack_tokenizer::{source_map::SourceMap, tokens::Location};
+use brack_transformer::{ast::AST, unparse::unparse};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvocationKind {
+    /// A macro of a plugin or of the `brack` module.
+    Macro,
+    /// A template defined by `<brack.define ...>`.
+    Template,
+}
+
+/// A macro or template call replaced by the expander.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Invocation {
+    pub kind: InvocationKind,
+    pub module: String,
+    pub command: String,
+    /// The call as written, or as produced by an outer expansion.
+    pub input: String,
+    pub location: Location,
+    /// The number of expansions in whose results the call is nested.
+    pub depth: usize,
+    /// The subtree returned for the call, before the macros in it are expanded.
+    /// This is `None` if the call failed.
+    pub output: Option<AST>,
+    /// The error of the call, which ends the expansion.
+    pub error: Option<String>,
+    pub duration_us: u128,
+}
+
+/// The invocations of an expansion in the order they were called,
+/// so an invocation is followed by the ones nested in its output.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Trace {
+    pub invocations: Vec<Invocation>,
+}
+
+impl Trace {
+    /// Renders one invocation per entry, indented by depth.
+    pub fn render(&self, source_map: &SourceMap) -> String {
+        let mut result = String::new();
+        for (index, invocation) in self.invocations.iter().enumerate() {
+            let indent = "  ".repeat(invocation.depth);
+            result.push_str(&format!(
+                "{}#{} {} {}.{} at {} ({}us)\n{}  {}\n{}  {}\n",
+                indent,
+                index + 1,
+                match invocation.kind {
+                    InvocationKind::Macro => "macro",
+                    InvocationKind::Template => "template",
+                },
+                invocation.module,
+                invocation.command,
+                source_map.render_location(&invocation.location),
+                invocation.duration_us,
+                indent,
+                invocation.input,
+                indent,
+                match (&invocation.output, &invocation.error) {
+                    (Some(output), _) => format!("=> {}", unparse(output).replace('\n', "\\n")),
+                    (None, error) => format!("!! {}", error.as_deref().unwrap_or_default()),
+                },
+            ));
+        }
+        result
+    }
+}
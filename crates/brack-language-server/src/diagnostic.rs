@@ -0,0 +1,121 @@
+use brack_tokenizer::{
+    diagnostic::{Diagnostic, Label, Severity},
+    line_index::{LineIndex, PositionEncoding},
+    source_map::SourceMap,
+};
+use lsp_types::{
+    Diagnostic as LspDiagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location,
+    NumberOrString, Url,
+};
+
+use crate::position::location_to_range;
+
+/// Converts a diagnostic of the compiler into the LSP one.
+/// Secondary labels become related information, and the fix is kept in `data`.
+/// Labels are resolved to the files they point to through `source_map`,
+/// and labels in unknown files are dropped.
+pub(crate) fn to_lsp_diagnostic(
+    diagnostic: &Diagnostic,
+    source_map: &SourceMap,
+    encoding: PositionEncoding,
+) -> LspDiagnostic {
+    let severity = match diagnostic.severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Info => DiagnosticSeverity::INFORMATION,
+        Severity::Hint => DiagnosticSeverity::HINT,
+    };
+    let range = diagnostic
+        .primary
+        .as_ref()
+        .and_then(|label| resolve_label(label, source_map, encoding))
+        .map(|location| location.range)
+        .unwrap_or_default();
+    let mut message = diagnostic.message.clone();
+    if let Some(help) = &diagnostic.help {
+        message.push_str(&format!("\nhelp: {}", help));
+    }
+    let related_information = diagnostic
+        .secondary
+        .iter()
+        .filter_map(|label| {
+            Some(DiagnosticRelatedInformation {
+                location: resolve_label(label, source_map, encoding)?,
+                message: label.message.clone().unwrap_or_default(),
+            })
+        })
+        .collect::<Vec<_>>();
+    LspDiagnostic {
+        range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String(diagnostic.code.clone())),
+        source: Some("brack".to_string()),
+        message,
+        related_information: (!related_information.is_empty()).then_some(related_information),
+        data: diagnostic
+            .fix
+            .as_ref()
+            .and_then(|fix| serde_json::to_value(fix).ok()),
+        ..Default::default()
+    }
+}
+
+fn resolve_label(
+    label: &Label,
+    source_map: &SourceMap,
+    encoding: PositionEncoding,
+) -> Option<Location> {
+    let file = source_map.get(label.location.file)?;
+    let uri = Url::from_file_path(&file.path).ok()?;
+    let index = LineIndex::new(&file.text);
+    Some(Location {
+        uri,
+        range: location_to_range(&index, &label.location, encoding),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use brack_tokenizer::{
+        diagnostic::Diagnostic,
+        line_index::PositionEncoding,
+        source_map::{FileId, SourceMap},
+        tokens::{Location, LocationData},
+    };
+    use lsp_types::Url;
+
+    use super::to_lsp_diagnostic;
+
+    fn location(file: FileId, line: usize, offset: usize) -> Location {
+        let data = LocationData {
+            line,
+            character: 0,
+            offset,
+        };
+        Location {
+            file,
+            start: data.clone(),
+            end: data,
+        }
+    }
+
+    #[test]
+    fn test_related_information_points_to_the_file_of_each_label() {
+        let mut source_map = SourceMap::new();
+        let main = source_map.add("/docs/index.[]", "a\n".to_string());
+        let chapter = source_map.add("/docs/chapter.[]", "b\nc\n".to_string());
+        let diagnostic = Diagnostic::error("B0000", "error", location(main, 0, 0))
+            .with_label(location(chapter, 1, 2), "defined here")
+            .with_label(location(FileId(2), 0, 0), "unknown file");
+
+        let lsp_diagnostic = to_lsp_diagnostic(&diagnostic, &source_map, PositionEncoding::Utf16);
+        let related_information = lsp_diagnostic.related_information.unwrap_or_default();
+        assert_eq!(related_information.len(), 1);
+        assert_eq!(
+            related_information[0].location.uri,
+            Url::from_file_path("/docs/chapter.[]").unwrap()
+        );
+        assert_eq!(related_information[0].location.range.start.line, 1);
+        assert_eq!(related_information[0].message, "defined here");
+    }
+}
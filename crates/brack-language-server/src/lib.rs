@@ -1,4 +1,6 @@
+mod diagnostic;
 mod notification;
+mod position;
 mod request;
 mod response;
 mod result;
@@ -1,10 +1,10 @@
 use anyhow::Result;
 use brack_parser::parse::parse;
-use brack_tokenizer::tokenize::tokenize;
+use brack_tokenizer::{source_map::SourceMap, tokenize::tokenize_file};
 use brack_transformer::transform::transform;
 use lsp_types::{Diagnostic, DidSaveTextDocumentParams};
 
-use crate::server::Server;
+use crate::{diagnostic::to_lsp_diagnostic, server::Server};
 
 impl Server {
     pub(crate) async fn handle_text_document_did_save(
@@ -20,38 +20,26 @@ impl Server {
             .to_str()
             .ok_or_else(|| anyhow::anyhow!("Invalid file path"))?;
 
-        let tokens = match tokenize(uri) {
+        let mut source_map = SourceMap::new();
+        let file = source_map.load(&file_path)?;
+        let tokens = match tokenize_file(&source_map, file) {
             Ok(tokens) => tokens,
             Err(e) => return self.log_message(&format!("Tokenize failed: {}", e)).await,
         };
-        let cst = parse(&tokens)?;
+        let (cst, parse_errors) = parse(&tokens);
         let (_, errors) = transform(&cst);
 
-        if errors.is_empty() {
+        if parse_errors.is_empty() && errors.is_empty() {
             let diagnostics: Vec<Diagnostic> = vec![];
             return self.send_publish_diagnostics(uri, &diagnostics).await;
         }
 
-        let mut diagnostics = vec![];
-        for error in errors {
-            let location = error.get_location();
-            let message = error.get_message();
-            let diagnostic = Diagnostic {
-                range: lsp_types::Range {
-                    start: lsp_types::Position {
-                        line: location.start.line as u32,
-                        character: location.start.character as u32,
-                    },
-                    end: lsp_types::Position {
-                        line: location.end.line as u32,
-                        character: location.end.character as u32,
-                    },
-                },
-                message,
-                ..Default::default()
-            };
-            diagnostics.push(diagnostic);
-        }
+        let diagnostics = parse_errors
+            .iter()
+            .map(|error| error.to_diagnostic())
+            .chain(errors.iter().map(|error| error.to_diagnostic()))
+            .map(|diagnostic| to_lsp_diagnostic(&diagnostic, &source_map, self.position_encoding))
+            .collect::<Vec<_>>();
         self.send_publish_diagnostics(uri, &diagnostics).await
     }
 }
@@ -0,0 +1,53 @@
+use brack_tokenizer::{
+    line_index::{LineIndex, PositionEncoding},
+    tokens::Location,
+};
+use lsp_types::{ClientCapabilities, Position, PositionEncodingKind, Range};
+
+/// Chooses the first encoding offered by the client that the server supports.
+/// LSP requires UTF-16 when the client does not offer any.
+pub(crate) fn negotiate_position_encoding(capabilities: &ClientCapabilities) -> PositionEncoding {
+    let offered = capabilities
+        .general
+        .as_ref()
+        .and_then(|general| general.position_encodings.as_ref());
+    if let Some(offered) = offered {
+        for kind in offered {
+            if *kind == PositionEncodingKind::UTF8 {
+                return PositionEncoding::Utf8;
+            } else if *kind == PositionEncodingKind::UTF16 {
+                return PositionEncoding::Utf16;
+            } else if *kind == PositionEncodingKind::UTF32 {
+                return PositionEncoding::Utf32;
+            }
+        }
+    }
+    PositionEncoding::Utf16
+}
+
+pub(crate) fn position_encoding_kind(encoding: PositionEncoding) -> PositionEncodingKind {
+    match encoding {
+        PositionEncoding::Utf8 => PositionEncodingKind::UTF8,
+        PositionEncoding::Utf16 => PositionEncodingKind::UTF16,
+        PositionEncoding::Utf32 => PositionEncodingKind::UTF32,
+    }
+}
+
+pub(crate) fn location_to_range(
+    index: &LineIndex,
+    location: &Location,
+    encoding: PositionEncoding,
+) -> Range {
+    let (start_line, start_character) = index.position(&location.start, encoding);
+    let (end_line, end_character) = index.position(&location.end, encoding);
+    Range {
+        start: Position {
+            line: start_line as u32,
+            character: start_character as u32,
+        },
+        end: Position {
+            line: end_line as u32,
+            character: end_character as u32,
+        },
+    }
+}
@@ -8,7 +8,11 @@ use lsp_types::{
 use serde::Deserialize;
 use serde_json::Value;
 
-use crate::{result::BLSResult, server::Server};
+use crate::{
+    position::{negotiate_position_encoding, position_encoding_kind},
+    result::BLSResult,
+    server::Server,
+};
 
 pub mod completion;
 pub mod semantic_tokens;
@@ -27,9 +31,11 @@ impl Server {
 
                 let params = InitializeParams::deserialize(msg["params"].clone())?;
                 self.client_capabilities = params.capabilities;
+                self.position_encoding = negotiate_position_encoding(&self.client_capabilities);
 
                 let response = InitializeResult {
                     capabilities: ServerCapabilities {
+                        position_encoding: Some(position_encoding_kind(self.position_encoding)),
                         text_document_sync: Some(TextDocumentSyncCapability::Kind(
                             TextDocumentSyncKind::FULL,
                         )),
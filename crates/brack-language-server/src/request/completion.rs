@@ -1,5 +1,5 @@
 use anyhow::Result;
-use brack_plugin::{metadata::Metadata, plugin::Plugin, plugins::Plugins, types::Type};
+use brack_plugin::{builtin, metadata::Metadata, plugin::Plugin, plugins::Plugins, types::Type};
 use lsp_types::{CompletionItem, CompletionParams, CompletionResponse, InsertTextFormat};
 
 use crate::server::Server;
@@ -9,14 +9,14 @@ fn build_completion_item(
     name: &str,
     typ: &Type,
     command_metadata: &Metadata,
-) -> CompletionItem {
+) -> Option<CompletionItem> {
     let insert_text = Some(match typ {
         Type::TInline => format!("{}.{} $0]", module_name, name),
         Type::TBlock => format!("{}.{} $0}}", module_name, name),
         Type::TAST => format!("{}.{} $0>", module_name, name),
-        _ => panic!("Invalid type"),
+        _ => return None,
     });
-    CompletionItem {
+    Some(CompletionItem {
         label: format!("{}.{}", module_name, name),
         detail: Some(format!(
             "Argument Types: {:?}\nReturn Type: {:?}",
@@ -25,7 +25,7 @@ fn build_completion_item(
         insert_text,
         insert_text_format: Some(InsertTextFormat::SNIPPET),
         ..CompletionItem::default()
-    }
+    })
 }
 
 impl Server {
@@ -33,11 +33,10 @@ impl Server {
         &self,
         params: CompletionParams,
     ) -> Result<Option<CompletionResponse>> {
-        if self.project.is_none() {
+        let Some(project) = self.project.as_ref() else {
             // BLS doesn't support single-file mode now.
             return Ok(None);
-        }
-        let project = self.project.as_ref().unwrap();
+        };
         let mut completion_items = vec![];
         let mut plugin_vec = vec![];
         for (name, (path, feature_flag)) in &project.plugins_metadata {
@@ -49,17 +48,25 @@ impl Server {
             .context
             .ok_or_else(|| anyhow::anyhow!("No context"))?
             .trigger_character;
-        if start.is_none() {
+        let Some(start) = start else {
             return Ok(None);
-        }
-        let start = start.unwrap();
-        for plugin in plugins.name_to_plugin.values() {
-            for ((name, typ), command_metadata) in plugin.signature_to_metadata.iter() {
+        };
+        // The built-in commands come first, as they are resolved before the plugins.
+        let modules =
+            std::iter::once((builtin::MODULE_NAME, &plugins.builtin.signature_to_metadata)).chain(
+                plugins
+                    .name_to_plugin
+                    .values()
+                    .map(|plugin| (plugin.name.as_str(), &plugin.signature_to_metadata)),
+            );
+        for (module_name, signature_to_metadata) in modules {
+            for ((name, typ), command_metadata) in signature_to_metadata.iter() {
                 if (start == *"[" && matches!(typ, Type::TInline))
                     || (start == *"{" && matches!(typ, Type::TBlock))
+                    || (start == *"<" && matches!(typ, Type::TAST))
                 {
-                    completion_items.push(build_completion_item(
-                        &plugin.name,
+                    completion_items.extend(build_completion_item(
+                        module_name,
                         name,
                         typ,
                         command_metadata,
@@ -1,5 +1,9 @@
 use anyhow::Result;
-use brack_tokenizer::{tokenize::tokenize, tokens::Token};
+use brack_tokenizer::{
+    line_index::{LineIndex, PositionEncoding},
+    tokenize::tokenize_str,
+    tokens::Token,
+};
 use lsp_types::{SemanticToken, SemanticTokenType, SemanticTokens, SemanticTokensParams};
 
 use crate::server::Server;
@@ -11,6 +15,9 @@ fn token_kind_to_type(token: &Token) -> u32 {
         Token::AngleBracketOpen(_) | Token::AngleBracketClose(_) => SemanticTokenType::MACRO,
         Token::CurlyBracketOpen(_) | Token::CurlyBracketClose(_) => SemanticTokenType::METHOD,
         Token::SquareBracketOpen(_) | Token::SquareBracketClose(_) => SemanticTokenType::FUNCTION,
+        Token::Comment(_, _) => SemanticTokenType::COMMENT,
+        Token::Raw(_, _) => SemanticTokenType::STRING,
+        Token::Equal(_) => SemanticTokenType::OPERATOR,
         _ => return 100, // no decoration
     };
     token_type_as_u32(typ)
@@ -44,30 +51,42 @@ fn token_type_as_u32(token_type: SemanticTokenType) -> u32 {
     }
 }
 
-fn separate(tokens: &Vec<Token>) -> Vec<SemanticToken> {
+fn separate(
+    tokens: &Vec<Token>,
+    index: &LineIndex,
+    encoding: PositionEncoding,
+) -> Vec<SemanticToken> {
     let mut semantic_tokens = Vec::new();
     let mut prev_line = 0;
     let mut prev_char = 0;
 
     for token in tokens {
         let location = token.get_location();
-        let delta_line = location.start.line as u32 - prev_line;
+        let (start_line, start_char) = index.position(&location.start, encoding);
+        let (end_line, end_char) = index.position(&location.end, encoding);
+        let (start_line, start_char) = (start_line as u32, start_char as u32);
+        let delta_line = start_line - prev_line;
         let delta_start = if delta_line == 0 {
-            location.start.character as u32 - prev_char
+            start_char - prev_char
         } else {
-            location.start.character as u32
+            start_char
+        };
+        let length = if end_line as u32 == start_line {
+            end_char as u32 - start_char
+        } else {
+            0
         };
 
         semantic_tokens.push(SemanticToken {
             delta_line,
             delta_start,
-            length: (location.end.character - location.start.character) as u32,
+            length,
             token_type: token_kind_to_type(token),
             token_modifiers_bitset: 0,
         });
 
-        prev_line = location.start.line as u32;
-        prev_char = location.start.character as u32;
+        prev_line = start_line;
+        prev_char = start_char;
     }
 
     semantic_tokens
@@ -88,7 +107,8 @@ impl Server {
             anyhow::anyhow!("Failed to convert file path to string: {:?}", file_path)
         })?;
 
-        let tokens = match tokenize(uri) {
+        let text = std::fs::read_to_string(uri)?;
+        let tokens = match tokenize_str(&text) {
             Ok(tokens) => tokens,
             Err(e) => {
                 self.log_message(&format!("Failed to tokenize file: {:?}", e))
@@ -97,7 +117,8 @@ impl Server {
             }
         };
 
-        let separated = separate(&tokens);
+        let index = LineIndex::new(&text);
+        let separated = separate(&tokens, &index, self.position_encoding);
         Ok(Some(SemanticTokens {
             result_id: None,
             data: separated,
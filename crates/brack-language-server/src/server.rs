@@ -2,6 +2,7 @@ use std::str::from_utf8;
 
 use anyhow::Result;
 use brack_project_manager::project::Project;
+use brack_tokenizer::line_index::PositionEncoding;
 use lsp_types::{ClientCapabilities, Diagnostic};
 use serde::Serialize;
 use serde_json::{from_str, json, Value};
@@ -10,6 +11,7 @@ use tokio::io::{stdin, stdout, AsyncReadExt, AsyncWriteExt};
 #[derive(Default)]
 pub struct Server {
     pub(crate) client_capabilities: ClientCapabilities,
+    pub(crate) position_encoding: PositionEncoding,
     pub(crate) project: Option<Project>,
 }
 
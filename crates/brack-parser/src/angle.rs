@@ -18,14 +18,14 @@ pub fn parse(tokens: &[Token]) -> Result<Parser> {
     };
 
     let (cst, mut tokens) = parse_angle_bracket_open(tokens)?;
-    result.add(cst);
+    result.add(cst)?;
 
     loop {
         if let Ok((cst, new_tokens)) = expr::parse(tokens) {
-            result.add(cst);
+            result.add(cst)?;
             tokens = new_tokens;
         } else if let Ok((cst, new_tokens)) = newline::parse(tokens) {
-            result.add(cst);
+            result.add(cst)?;
             tokens = new_tokens;
         } else {
             break;
@@ -39,13 +39,14 @@ pub fn parse(tokens: &[Token]) -> Result<Parser> {
     };
 
     let tokens = if let Ok((cst, tokens)) = parse_angle_bracket_close(tokens) {
-        result.add(cst);
+        result.add(cst)?;
         tokens
     } else {
         tokens
     };
 
     result.set_location(Location {
+        file: bracket_open_location.file,
         start: bracket_open_location.start,
         end: bracket_close_location.end,
     });
@@ -103,32 +104,32 @@ mod tests {
         let (cst, tokens) = super::parse(&tokens)?;
         assert!(matches_kind(&cst, &new_angle()));
         assert!(matches_kind(
-            &cst.children()[0],
+            &cst.try_children()?[0],
             &new_angle_bracket_open(mock_location())
         ));
-        assert!(matches_kind(&cst.children()[1], &new_expr()));
+        assert!(matches_kind(&cst.try_children()?[1], &new_expr()));
         assert!(matches_kind(
-            &cst.children()[1].children()[0],
+            &cst.try_children()?[1].try_children()?[0],
             &new_module("std".to_string(), mock_location())
         ));
         assert!(matches_kind(
-            &cst.children()[1].children()[1],
+            &cst.try_children()?[1].try_children()?[1],
             &new_dot(mock_location())
         ));
         assert!(matches_kind(
-            &cst.children()[1].children()[2],
+            &cst.try_children()?[1].try_children()?[2],
             &new_ident("*".to_string(), mock_location())
         ));
         assert!(matches_kind(
-            &cst.children()[1].children()[3],
+            &cst.try_children()?[1].try_children()?[3],
             &new_whitespace(mock_location())
         ));
         assert!(matches_kind(
-            &cst.children()[1].children()[4],
+            &cst.try_children()?[1].try_children()?[4],
             &new_text("Hello!".to_string(), mock_location())
         ));
         assert!(matches_kind(
-            &cst.children()[2],
+            &cst.try_children()?[2],
             &new_angle_bracket_close(mock_location())
         ));
         assert_eq!(tokens.len(), 0);
@@ -149,28 +150,28 @@ mod tests {
         let (cst, tokens) = super::parse(&tokens)?;
         assert!(matches_kind(&cst, &new_angle()));
         assert!(matches_kind(
-            &cst.children()[0],
+            &cst.try_children()?[0],
             &new_angle_bracket_open(mock_location())
         ));
-        assert!(matches_kind(&cst.children()[1], &new_expr()));
+        assert!(matches_kind(&cst.try_children()?[1], &new_expr()));
         assert!(matches_kind(
-            &cst.children()[1].children()[0],
+            &cst.try_children()?[1].try_children()?[0],
             &new_module("std".to_string(), mock_location())
         ));
         assert!(matches_kind(
-            &cst.children()[1].children()[1],
+            &cst.try_children()?[1].try_children()?[1],
             &new_dot(mock_location())
         ));
         assert!(matches_kind(
-            &cst.children()[1].children()[2],
+            &cst.try_children()?[1].try_children()?[2],
             &new_ident("*".to_string(), mock_location())
         ));
         assert!(matches_kind(
-            &cst.children()[1].children()[3],
+            &cst.try_children()?[1].try_children()?[3],
             &new_whitespace(mock_location())
         ));
         assert!(matches_kind(
-            &cst.children()[1].children()[4],
+            &cst.try_children()?[1].try_children()?[4],
             &new_text("Hello!".to_string(), mock_location())
         ));
         assert_eq!(tokens.len(), 1);
@@ -0,0 +1,45 @@
+use anyhow::{bail, Result};
+use brack_tokenizer::tokens::Token;
+
+use crate::{cst::new_comment, parser::Parser};
+
+// comment
+pub fn parse(tokens: &[Token]) -> Result<Parser> {
+    if let Some(token) = tokens.first() {
+        match token {
+            Token::Comment(text, location) => {
+                return Ok((new_comment(text.clone(), location.clone()), &tokens[1..]));
+            }
+            token => bail!("Expected comment token, found {:?}", token),
+        }
+    }
+    bail!("Expected comment token, found none");
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_tokenizer::tokens::{mock_location, Token};
+
+    use crate::cst::{matches_kind, new_comment};
+
+    #[test]
+    fn test_comment_parse_only_comment() -> Result<()> {
+        let tokens = vec![Token::Comment(" note".to_string(), mock_location())];
+        let (cst, tokens) = super::parse(&tokens)?;
+        assert_eq!(tokens.len(), 0);
+        assert!(matches_kind(
+            &cst,
+            &new_comment(" note".to_string(), mock_location())
+        ));
+        assert_eq!(cst.value(), Some(" note".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_comment_parse_failure() {
+        let tokens = vec![Token::Text("// note".to_string(), mock_location())];
+        let result = super::parse(&tokens);
+        assert!(result.is_err());
+    }
+}
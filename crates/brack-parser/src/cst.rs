@@ -1,6 +1,7 @@
 use std::fmt::{self};
 
-use brack_tokenizer::tokens::{merge_location, mock_location, Location};
+use anyhow::Result;
+use brack_tokenizer::tokens::{merge_location, mock_location, Location, RawBlock};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -18,6 +19,13 @@ pub struct LeafNode {
     pub location: Location,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct RawNode {
+    pub id: String,
+    pub raw: RawBlock,
+    pub location: Location,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub enum CST {
     Document(InnerNode),
@@ -36,14 +44,33 @@ pub enum CST {
     Module(LeafNode),
     Ident(LeafNode),
     Text(LeafNode),
+    Comment(LeafNode),
+    FrontMatter(LeafNode),
+    Raw(RawNode),
     Whitespace(LeafNode),
     Newline(LeafNode),
     Dot(LeafNode),
     Comma(LeafNode),
+    Equal(LeafNode),
     EOF(LeafNode),
     Invalid(LeafNode),
 }
 
+impl InnerNode {
+    pub fn new(location: Location) -> Self {
+        InnerNode {
+            id: Uuid::new_v4().to_string(),
+            children: vec![],
+            location,
+        }
+    }
+
+    pub fn add(&mut self, cst: CST) {
+        self.location = merge_location(&self.location, &cst.location());
+        self.children.push(cst);
+    }
+}
+
 impl fmt::Display for CST {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.display_with_indent(f, 0)
@@ -51,7 +78,8 @@ impl fmt::Display for CST {
 }
 
 impl CST {
-    pub fn children(&self) -> &Vec<CST> {
+    /// Returns the children of an inner node, or an error for leaf nodes.
+    pub fn try_children(&self) -> Result<&Vec<CST>> {
         match self {
             CST::Document(node)
             | CST::Stmt(node)
@@ -59,11 +87,16 @@ impl CST {
             | CST::BackSlash(node)
             | CST::Angle(node)
             | CST::Curly(node)
-            | CST::Square(node) => &node.children,
-            _ => panic!("This node does not have children"),
+            | CST::Square(node) => Ok(&node.children),
+            _ => anyhow::bail!("This node does not have children"),
         }
     }
 
+    /// Returns the children of an inner node, or `None` for leaf nodes.
+    pub fn children(&self) -> Option<&Vec<CST>> {
+        self.try_children().ok()
+    }
+
     pub fn location(&self) -> Location {
         match self {
             CST::Document(node)
@@ -82,12 +115,16 @@ impl CST {
             | CST::Module(leaf)
             | CST::Ident(leaf)
             | CST::Text(leaf)
+            | CST::Comment(leaf)
+            | CST::FrontMatter(leaf)
             | CST::Whitespace(leaf)
             | CST::Newline(leaf)
             | CST::Dot(leaf)
             | CST::Comma(leaf)
+            | CST::Equal(leaf)
             | CST::Invalid(leaf)
             | CST::EOF(leaf) => leaf.location.clone(),
+            CST::Raw(raw) => raw.location.clone(),
         }
     }
 
@@ -111,15 +148,36 @@ impl CST {
             | CST::Module(leaf)
             | CST::Ident(leaf)
             | CST::Text(leaf)
+            | CST::Comment(leaf)
+            | CST::FrontMatter(leaf)
             | CST::Whitespace(leaf)
             | CST::Newline(leaf)
             | CST::Dot(leaf)
             | CST::Comma(leaf)
+            | CST::Equal(leaf)
             | CST::Invalid(leaf)
             | CST::EOF(leaf) => leaf.location = location,
+            CST::Raw(raw) => raw.location = location,
         }
     }
 
+    /// Returns the value of a leaf node, or an error for inner nodes and leaves without a value.
+    pub fn try_value(&self) -> Result<String> {
+        match self {
+            CST::Document(_)
+            | CST::Stmt(_)
+            | CST::Expr(_)
+            | CST::BackSlash(_)
+            | CST::Angle(_)
+            | CST::Curly(_)
+            | CST::Square(_) => anyhow::bail!("This node does not have a value"),
+            _ => self
+                .value()
+                .ok_or_else(|| anyhow::anyhow!("No value found")),
+        }
+    }
+
+    /// Returns the value of a leaf node, or `None` for inner nodes and leaves without a value.
     pub fn value(&self) -> Option<String> {
         match self {
             CST::AngleBracketOpen(leaf)
@@ -131,16 +189,28 @@ impl CST {
             | CST::Module(leaf)
             | CST::Ident(leaf)
             | CST::Text(leaf)
+            | CST::Comment(leaf)
+            | CST::FrontMatter(leaf)
             | CST::Whitespace(leaf)
             | CST::Newline(leaf)
             | CST::Dot(leaf)
             | CST::Comma(leaf)
-            | CST::EOF(leaf) => leaf.value.clone(),
-            _ => panic!("This node does not have a value"),
+            | CST::Equal(leaf)
+            | CST::EOF(leaf)
+            | CST::Invalid(leaf) => leaf.value.clone(),
+            CST::Raw(raw) => Some(raw.raw.content.clone()),
+            CST::Document(_)
+            | CST::Stmt(_)
+            | CST::Expr(_)
+            | CST::BackSlash(_)
+            | CST::Angle(_)
+            | CST::Curly(_)
+            | CST::Square(_) => None,
         }
     }
 
-    pub fn add(&mut self, cst: CST) {
+    /// Adds a child to an inner node, or returns an error for leaf nodes.
+    pub fn add(&mut self, cst: CST) -> Result<()> {
         match self {
             CST::Document(node)
             | CST::Stmt(node)
@@ -149,34 +219,10 @@ impl CST {
             | CST::Angle(node)
             | CST::Square(node)
             | CST::Curly(node) => {
-                node.children.push(cst.clone());
-                let location_children = match cst {
-                    CST::Document(node)
-                    | CST::Stmt(node)
-                    | CST::Expr(node)
-                    | CST::BackSlash(node)
-                    | CST::Angle(node)
-                    | CST::Curly(node)
-                    | CST::Square(node) => node.location,
-                    CST::AngleBracketOpen(leaf)
-                    | CST::AngleBracketClose(leaf)
-                    | CST::CurlyBracketOpen(leaf)
-                    | CST::CurlyBracketClose(leaf)
-                    | CST::SquareBracketOpen(leaf)
-                    | CST::SquareBracketClose(leaf)
-                    | CST::Module(leaf)
-                    | CST::Ident(leaf)
-                    | CST::Text(leaf)
-                    | CST::Whitespace(leaf)
-                    | CST::Newline(leaf)
-                    | CST::Dot(leaf)
-                    | CST::Comma(leaf)
-                    | CST::Invalid(leaf)
-                    | CST::EOF(leaf) => leaf.location,
-                };
-                node.location = merge_location(&node.location, &location_children);
+                node.add(cst);
+                Ok(())
             }
-            _ => panic!("This node does not have children"),
+            _ => anyhow::bail!("This node does not have children"),
         }
     }
 
@@ -264,12 +310,32 @@ impl CST {
                 "{}Text(id: {}, value: {:?})",
                 indent_str, leaf.id, leaf.value
             ),
+            CST::Comment(leaf) => write!(
+                f,
+                "{}Comment(id: {}, value: {:?})",
+                indent_str, leaf.id, leaf.value
+            ),
+            CST::FrontMatter(leaf) => write!(
+                f,
+                "{}FrontMatter(id: {}, value: {:?})",
+                indent_str, leaf.id, leaf.value
+            ),
             CST::Whitespace(leaf) => write!(f, "{}Whitespace(id: {})", indent_str, leaf.id),
             CST::Newline(leaf) => write!(f, "{}Newline(id: {})", indent_str, leaf.id),
             CST::BackSlash(leaf) => write!(f, "{}BackSlash(id: {})", indent_str, leaf.id),
             CST::Dot(leaf) => write!(f, "{}Dot(id: {})", indent_str, leaf.id),
             CST::Comma(leaf) => write!(f, "{}Comma(id: {})", indent_str, leaf.id),
-            CST::Invalid(leaf) => write!(f, "{}Invalid(id: {})", indent_str, leaf.id),
+            CST::Equal(leaf) => write!(f, "{}Equal(id: {})", indent_str, leaf.id),
+            CST::Raw(raw) => write!(
+                f,
+                "{}Raw(id: {}, info: {:?}, content: {:?})",
+                indent_str, raw.id, raw.raw.info, raw.raw.content
+            ),
+            CST::Invalid(leaf) => write!(
+                f,
+                "{}Invalid(id: {}, value: {:?})",
+                indent_str, leaf.id, leaf.value
+            ),
             CST::EOF(leaf) => write!(f, "{}EOF(id: {})", indent_str, leaf.id),
         }
     }
@@ -293,12 +359,17 @@ pub fn matches_kind(cst: &CST, kind: &CST) -> bool {
             | (CST::Module(_), CST::Module(_))
             | (CST::Ident(_), CST::Ident(_))
             | (CST::Text(_), CST::Text(_))
+            | (CST::Comment(_), CST::Comment(_))
+            | (CST::FrontMatter(_), CST::FrontMatter(_))
+            | (CST::Raw(_), CST::Raw(_))
             | (CST::Whitespace(_), CST::Whitespace(_))
             | (CST::Newline(_), CST::Newline(_))
             | (CST::BackSlash(_), CST::BackSlash(_))
             | (CST::Dot(_), CST::Dot(_))
             | (CST::Comma(_), CST::Comma(_))
+            | (CST::Equal(_), CST::Equal(_))
             | (CST::EOF(_), CST::EOF(_))
+            | (CST::Invalid(_), CST::Invalid(_))
     )
 }
 
@@ -334,10 +405,10 @@ pub fn new_eof(location: Location) -> CST {
     })
 }
 
-pub fn new_invalid(location: Location) -> CST {
+pub fn new_invalid(value: String, location: Location) -> CST {
     CST::Invalid(LeafNode {
         id: Uuid::new_v4().to_string(),
-        value: None,
+        value: Some(value),
         location,
     })
 }
@@ -374,6 +445,30 @@ pub fn new_text(value: String, location: Location) -> CST {
     })
 }
 
+pub fn new_comment(value: String, location: Location) -> CST {
+    CST::Comment(LeafNode {
+        id: Uuid::new_v4().to_string(),
+        value: Some(value),
+        location,
+    })
+}
+
+pub fn new_front_matter(value: String, location: Location) -> CST {
+    CST::FrontMatter(LeafNode {
+        id: Uuid::new_v4().to_string(),
+        value: Some(value),
+        location,
+    })
+}
+
+pub fn new_raw(raw: RawBlock, location: Location) -> CST {
+    CST::Raw(RawNode {
+        id: Uuid::new_v4().to_string(),
+        raw,
+        location,
+    })
+}
+
 pub fn new_ident(value: String, location: Location) -> CST {
     CST::Ident(LeafNode {
         id: Uuid::new_v4().to_string(),
@@ -422,6 +517,14 @@ pub fn new_comma(location: Location) -> CST {
     })
 }
 
+pub fn new_equal(location: Location) -> CST {
+    CST::Equal(LeafNode {
+        id: Uuid::new_v4().to_string(),
+        value: None,
+        location,
+    })
+}
+
 pub fn new_dot(location: Location) -> CST {
     CST::Dot(LeafNode {
         id: Uuid::new_v4().to_string(),
@@ -477,3 +580,22 @@ pub fn new_curly_bracket_close(location: Location) -> CST {
         location,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use brack_tokenizer::tokens::mock_location;
+
+    use super::{new_comma, new_expr, new_text};
+
+    #[test]
+    fn test_accessors_do_not_panic() {
+        let mut text = new_text("Hello".to_string(), mock_location());
+        assert!(text.children().is_none());
+        assert!(text.add(new_comma(mock_location())).is_err());
+
+        let mut expr = new_expr();
+        assert!(expr.add(text).is_ok());
+        assert_eq!(expr.children().map(|children| children.len()), Some(1));
+        assert_eq!(expr.value(), None);
+    }
+}
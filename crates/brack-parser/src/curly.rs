@@ -18,14 +18,14 @@ pub fn parse(tokens: &[Token]) -> Result<Parser> {
     };
 
     let (cst, mut tokens) = parse_curly_bracket_open(tokens)?;
-    result.add(cst);
+    result.add(cst)?;
 
     loop {
         if let Ok((cst, new_tokens)) = expr::parse(tokens) {
-            result.add(cst);
+            result.add(cst)?;
             tokens = new_tokens;
         } else if let Ok((cst, new_tokens)) = newline::parse(tokens) {
-            result.add(cst);
+            result.add(cst)?;
             tokens = new_tokens;
         } else {
             break;
@@ -39,13 +39,14 @@ pub fn parse(tokens: &[Token]) -> Result<Parser> {
     };
 
     let tokens = if let Ok((cst, tokens)) = parse_curly_bracket_close(tokens) {
-        result.add(cst);
+        result.add(cst)?;
         tokens
     } else {
         tokens
     };
 
     result.set_location(Location {
+        file: bracket_open_location.file,
         start: bracket_open_location.start,
         end: bracket_close_location.end,
     });
@@ -0,0 +1,40 @@
+use anyhow::{bail, Result};
+use brack_tokenizer::tokens::Token;
+
+use crate::{cst::new_equal, parser::Parser};
+
+pub fn parse(tokens: &[Token]) -> Result<Parser> {
+    if let Some(token) = tokens.first() {
+        match token {
+            Token::Equal(location) => {
+                return Ok((new_equal(location.clone()), &tokens[1..]));
+            }
+            token => bail!("Expected equal token, found {:?}", token),
+        }
+    }
+    bail!("Expected equal, found none");
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_tokenizer::tokens::{mock_location, Token};
+
+    use crate::cst::{matches_kind, new_equal};
+
+    #[test]
+    fn test_equal_parse_only_equal() -> Result<()> {
+        let tokens = vec![Token::Equal(mock_location())];
+        let (cst, tokens) = super::parse(&tokens)?;
+        assert_eq!(tokens.len(), 0);
+        assert!(matches_kind(&cst, &new_equal(mock_location())));
+        Ok(())
+    }
+
+    #[test]
+    fn test_equal_parse_failure() {
+        let tokens = vec![Token::AngleBracketOpen(mock_location())];
+        let result = super::parse(&tokens);
+        assert!(result.is_err());
+    }
+}
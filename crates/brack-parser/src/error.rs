@@ -0,0 +1,55 @@
+use std::fmt::{self, Display, Formatter};
+
+use brack_tokenizer::{diagnostic::Diagnostic, tokens::Location};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ParseError {
+    UnexpectedToken(Location),
+}
+
+impl ParseError {
+    pub fn get_location(&self) -> Location {
+        match self {
+            Self::UnexpectedToken(location) => location.clone(),
+        }
+    }
+
+    pub fn get_message(&self) -> String {
+        match self {
+            Self::UnexpectedToken(_) => "Unexpected token".to_string(),
+        }
+    }
+
+    pub fn get_code(&self) -> &'static str {
+        match self {
+            Self::UnexpectedToken(_) => "B0001",
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic =
+            Diagnostic::error(self.get_code(), self.get_message(), self.get_location());
+        match self {
+            Self::UnexpectedToken(_) => diagnostic.with_help(
+                "escape brackets, dots and commas with a backslash to write them as text",
+            ),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let location = self.get_location();
+        let message = self.get_message();
+        write!(
+            f,
+            "Error at line {}, column {} to line {}, column {}: {}",
+            location.start.line,
+            location.start.character,
+            location.end.line,
+            location.end.character,
+            message
+        )
+    }
+}
@@ -9,7 +9,7 @@ use crate::cst::InnerNode;
 use crate::cst::CST;
 use crate::parser::Parser;
 
-// backslash (dot | comma | bracket_open | bracket_close | backslash | .)
+// backslash (dot | comma | bracket_open | bracket_close | backslash | slash | .)
 pub fn parse(tokens: &[Token]) -> Result<Parser> {
     if let Some(token) = tokens.first() {
         match token {
@@ -53,6 +53,15 @@ pub fn parse(tokens: &[Token]) -> Result<Parser> {
                             tokens = &tokens[1..];
                             Ok(new_text(",".to_string(), location.clone()))
                         }
+                        Token::Equal(location) => {
+                            tokens = &tokens[1..];
+                            Ok(new_text("=".to_string(), location.clone()))
+                        }
+                        // `\//` at the beginning of a line is not a comment but a text.
+                        Token::Text(text, location) if text.starts_with('/') => {
+                            tokens = &tokens[1..];
+                            Ok(new_text(text.clone(), location.clone()))
+                        }
                         _ => Err(()),
                     };
                     let children = match escaped_node {
@@ -93,7 +102,7 @@ mod tests {
         assert_eq!(tokens.len(), 0);
         assert!(matches_kind(&cst, &new_backslash(mock_location())));
         assert!(matches_kind(
-            &cst.children()[0],
+            &cst.try_children()?[0],
             &new_text(String::from("."), mock_location())
         ));
         Ok(())
@@ -109,7 +118,7 @@ mod tests {
         assert_eq!(tokens.len(), 0);
         assert!(matches_kind(&cst, &new_backslash(mock_location())));
         assert!(matches_kind(
-            &cst.children()[0],
+            &cst.try_children()?[0],
             &new_text(String::from(","), mock_location())
         ));
         Ok(())
@@ -125,7 +134,7 @@ mod tests {
         assert_eq!(tokens.len(), 0);
         assert!(matches_kind(&cst, &new_backslash(mock_location())));
         assert!(matches_kind(
-            &cst.children()[0],
+            &cst.try_children()?[0],
             &new_text(String::from("<"), mock_location())
         ));
         Ok(())
@@ -141,7 +150,7 @@ mod tests {
         assert_eq!(tokens.len(), 0);
         assert!(matches_kind(&cst, &new_backslash(mock_location())));
         assert!(matches_kind(
-            &cst.children()[0],
+            &cst.try_children()?[0],
             &new_text(String::from(">"), mock_location())
         ));
         Ok(())
@@ -157,12 +166,28 @@ mod tests {
         assert_eq!(tokens.len(), 0);
         assert!(matches_kind(&cst, &new_backslash(mock_location())));
         assert!(matches_kind(
-            &cst.children()[0],
+            &cst.try_children()?[0],
             &new_text(String::from("\\"), mock_location())
         ));
         Ok(())
     }
 
+    #[test]
+    fn test_escaped_parse_valid_slash() -> Result<()> {
+        let tokens = vec![
+            Token::BackSlash(mock_location()),
+            Token::Text("//not a comment".to_string(), mock_location()),
+        ];
+        let (cst, tokens) = super::parse(&tokens)?;
+        assert_eq!(tokens.len(), 0);
+        assert!(matches_kind(&cst, &new_backslash(mock_location())));
+        assert_eq!(
+            cst.try_children()?[0].value(),
+            Some("//not a comment".to_string())
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_escaped_parse_valid_text() -> Result<()> {
         let tokens = vec![
@@ -172,7 +197,7 @@ mod tests {
         let (cst, tokens) = super::parse(&tokens)?;
         assert_eq!(tokens.len(), 1);
         assert!(matches_kind(&cst, &new_backslash(mock_location())));
-        assert_eq!(cst.children().len(), 0);
+        assert_eq!(cst.try_children()?.len(), 0);
         Ok(())
     }
 
@@ -0,0 +1,180 @@
+use crate::{cst::CST, print::print};
+
+/// Formats a CST into the canonical layout.
+/// Statements are separated by a blank line and the document ends with a newline.
+/// In brackets, exactly one space follows the identifier and each comma,
+/// and no space precedes a comma, a newline or the closing bracket.
+/// Named arguments are written as `name=value`.
+/// Escapes and texts are kept as they are.
+pub fn format(cst: &CST) -> String {
+    let mut source = String::new();
+    format_to(cst, &mut source);
+    source
+}
+
+fn format_to(cst: &CST, source: &mut String) {
+    match cst {
+        CST::Document(node) => {
+            // The front matter is kept as it is.
+            if let Some(CST::FrontMatter(leaf)) = node.children.first() {
+                source.push_str(leaf.value.as_deref().unwrap_or_default());
+            }
+            let front_matter_end = source.len();
+            let stmts = node
+                .children
+                .iter()
+                .filter(|child| matches!(child, CST::Stmt(_)))
+                .collect::<Vec<_>>();
+            for (i, stmt) in stmts.iter().enumerate() {
+                if i > 0 {
+                    source.push_str("\n\n");
+                }
+                format_to(stmt, source);
+            }
+            // An unclosed bracket may have consumed the trailing newlines.
+            let end = source[front_matter_end..].trim_end_matches('\n').len();
+            source.truncate(front_matter_end + end);
+            if !stmts.is_empty() {
+                source.push('\n');
+            }
+        }
+        CST::Stmt(node) | CST::Expr(node) => {
+            for child in &node.children {
+                format_to(child, source);
+            }
+        }
+        CST::Angle(node) | CST::Curly(node) | CST::Square(node) => {
+            let mut items = vec![];
+            for child in &node.children {
+                match child {
+                    CST::Expr(expr) => items.extend(expr.children.iter()),
+                    child => items.push(child),
+                }
+            }
+            let mut space = false;
+            let mut line_start = true;
+            let mut glued = false;
+            for item in items {
+                match item {
+                    CST::Whitespace(_) => space = true,
+                    CST::Newline(_) => {
+                        source.push('\n');
+                        space = false;
+                        line_start = true;
+                    }
+                    CST::Comma(_) => {
+                        source.push(',');
+                        space = true;
+                        line_start = false;
+                    }
+                    CST::Equal(_) => {
+                        source.push('=');
+                        space = false;
+                        glued = true;
+                        line_start = false;
+                    }
+                    CST::AngleBracketOpen(_)
+                    | CST::CurlyBracketOpen(_)
+                    | CST::SquareBracketOpen(_) => {
+                        source.push_str(&print(item));
+                        line_start = true;
+                    }
+                    CST::AngleBracketClose(_)
+                    | CST::CurlyBracketClose(_)
+                    | CST::SquareBracketClose(_) => source.push_str(&print(item)),
+                    item => {
+                        if space && !line_start && !glued {
+                            source.push(' ');
+                        }
+                        format_to(item, source);
+                        space = false;
+                        glued = false;
+                        line_start = false;
+                    }
+                }
+            }
+        }
+        cst => source.push_str(&print(cst)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::Result;
+    use brack_tokenizer::tokenize::tokenize_str;
+
+    use crate::parse::parse;
+
+    use super::format;
+
+    fn format_str(text: &str) -> Result<String> {
+        let tokens = tokenize_str(text)?;
+        let (cst, _) = parse(&tokens);
+        Ok(format(&cst))
+    }
+
+    #[test]
+    fn test_format_spacing_in_brackets() -> Result<()> {
+        assert_eq!(format_str("[std.*   a  ,b ,  c ]")?, "[std.* a, b, c]\n");
+        assert_eq!(
+            format_str("{std.**   [std.*  x ]}")?,
+            "{std.** [std.* x]}\n"
+        );
+        assert_eq!(format_str("<std.* a,\n  b\n>")?, "<std.* a,\nb\n>\n");
+        assert_eq!(
+            format_str("[std.anchor a ,title =  b]")?,
+            "[std.anchor a, title=b]\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_blank_lines_between_stmts() -> Result<()> {
+        assert_eq!(
+            format_str("Hello\nWorld\n\n\n\nfoo\n\n")?,
+            "Hello\nWorld\n\nfoo\n"
+        );
+        assert_eq!(format_str("")?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_keeps_bodies_of_block_commands() -> Result<()> {
+        let text =
+            "{std.callout  note ,title=Tips\nHello,  world.\n\n{std.column\n[std.*  a ]\n}\n}";
+        assert_eq!(
+            format_str(text)?,
+            "{std.callout note, title=Tips\nHello,  world.\n\n{std.column\n[std.* a]\n}\n}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_preserves_escapes_and_comments() -> Result<()> {
+        let text = "// note\n\\[not a command\\]  text \\\\\n\n[std.* a\\, b]\n";
+        assert_eq!(format_str(text)?, text);
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_keeps_front_matter() -> Result<()> {
+        assert_eq!(
+            format_str("+++\ntitle = \"Brack\"\n+++\n\n\n[std.*   a ]\n\n")?,
+            "+++\ntitle = \"Brack\"\n+++\n[std.* a]\n"
+        );
+        assert_eq!(format_str("+++\n+++\n")?, "+++\n+++\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_is_idempotent_over_tokenizer_fixtures() -> Result<()> {
+        for entry in fs::read_dir("../brack-tokenizer/test")? {
+            let path = entry?.path();
+            let formatted = format_str(&fs::read_to_string(&path)?)?;
+            assert_eq!(format_str(&formatted)?, formatted, "{}", path.display());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cst::CST;
+
+/// How the ids of CST and AST nodes are assigned.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdStrategy {
+    /// Ids are derived from the document path and the position of each node,
+    /// so two builds of the same input are identical.
+    #[default]
+    Deterministic,
+    /// Ids are random UUIDs.
+    Random,
+}
+
+/// Reassigns the ids of all CST nodes in the same way as the ids of AST nodes:
+/// a node's id is the hash of `path` followed by the child indices from the root.
+pub fn assign_ids(cst: &mut CST, path: &str, strategy: IdStrategy) {
+    match strategy {
+        IdStrategy::Deterministic => aux(cst, path_hash(path)),
+        // Nodes already have random ids when they are created.
+        IdStrategy::Random => (),
+    }
+}
+
+fn aux(cst: &mut CST, id: String) {
+    match cst {
+        CST::Document(node)
+        | CST::Stmt(node)
+        | CST::Expr(node)
+        | CST::Angle(node)
+        | CST::Curly(node)
+        | CST::Square(node)
+        | CST::BackSlash(node) => {
+            for (i, child) in node.children.iter_mut().enumerate() {
+                aux(child, format!("{}-{}", id, i));
+            }
+            node.id = id;
+        }
+        CST::AngleBracketOpen(leaf)
+        | CST::AngleBracketClose(leaf)
+        | CST::SquareBracketOpen(leaf)
+        | CST::SquareBracketClose(leaf)
+        | CST::CurlyBracketOpen(leaf)
+        | CST::CurlyBracketClose(leaf)
+        | CST::Module(leaf)
+        | CST::Ident(leaf)
+        | CST::Text(leaf)
+        | CST::Comment(leaf)
+        | CST::FrontMatter(leaf)
+        | CST::Whitespace(leaf)
+        | CST::Newline(leaf)
+        | CST::Dot(leaf)
+        | CST::Comma(leaf)
+        | CST::Equal(leaf)
+        | CST::EOF(leaf)
+        | CST::Invalid(leaf) => leaf.id = id,
+        CST::Raw(raw) => raw.id = id,
+    }
+}
+
+/// The id of the root node of the document at `path`.
+/// 64-bit FNV-1a is used, which is stable across platforms and compiler versions.
+pub fn path_hash(path: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in path.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// The path ids are derived from, so that `./a.[]` and `a.[]` give the same ids.
+/// The path is made relative to `root` if it is inside it, and `/` is used as the separator.
+pub fn id_path<P: AsRef<Path>, Q: AsRef<Path>>(path: P, root: Q) -> String {
+    let path = path.as_ref();
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let relative = root
+        .as_ref()
+        .canonicalize()
+        .ok()
+        .and_then(|root| canonical.strip_prefix(root).ok().map(Path::to_path_buf))
+        .unwrap_or(canonical);
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_tokenizer::tokenize::tokenize_str;
+
+    use crate::parse::parse;
+
+    use super::{assign_ids, id_path, IdStrategy};
+
+    #[test]
+    fn test_cst_ids_are_stable() -> Result<()> {
+        let tokens = tokenize_str("Hello, [std.* World]!")?;
+        let (mut cst1, _) = parse(&tokens);
+        let (mut cst2, _) = parse(&tokens);
+        assign_ids(&mut cst1, "docs/index.[]", IdStrategy::Deterministic);
+        assign_ids(&mut cst2, "docs/index.[]", IdStrategy::Deterministic);
+        assert_eq!(cst1, cst2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_id_path_is_normalized() -> Result<()> {
+        let root = std::env::current_dir()?;
+        assert_eq!(id_path("Cargo.toml", &root), "Cargo.toml");
+        assert_eq!(id_path("./Cargo.toml", &root), "Cargo.toml");
+        assert_eq!(id_path(root.join("src/../Cargo.toml"), &root), "Cargo.toml");
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::{bail, Result};
+use brack_tokenizer::tokens::{Location, Token};
+
+use crate::{cst::new_invalid, parser::Parser};
+
+// (!(newline | EOF))+
+pub fn parse(tokens: &[Token]) -> Result<Parser> {
+    let mut source = String::new();
+    let (file, start) = match tokens.first() {
+        Some(Token::NewLine(_)) | Some(Token::EOF(_)) | None => {
+            bail!("Expected tokens other than newline and EOF, found none")
+        }
+        Some(token) => (token.get_location().file, token.get_location().start),
+    };
+    let mut end = start.clone();
+    let mut tokens = tokens;
+    while let Some(token) = tokens.first() {
+        match token {
+            Token::NewLine(_) | Token::EOF(_) => break,
+            Token::Text(text, _)
+            | Token::Module(text, _)
+            | Token::Ident(text, _)
+            | Token::FrontMatter(text, _) => source.push_str(text),
+            Token::Comment(text, _) => {
+                source.push_str("//");
+                source.push_str(text);
+            }
+            Token::Raw(raw, _) => source.push_str(&raw.source()),
+            Token::Empty(_) => (),
+            Token::WhiteSpace(_) => source.push(' '),
+            Token::Dot(_) => source.push('.'),
+            Token::Comma(_) => source.push(','),
+            Token::Equal(_) => source.push('='),
+            Token::BackSlash(_) => source.push('\\'),
+            Token::AngleBracketOpen(_) => source.push('<'),
+            Token::AngleBracketClose(_) => source.push('>'),
+            Token::SquareBracketOpen(_) => source.push('['),
+            Token::SquareBracketClose(_) => source.push(']'),
+            Token::CurlyBracketOpen(_) => source.push('{'),
+            Token::CurlyBracketClose(_) => source.push('}'),
+        }
+        end = token.get_location().end;
+        tokens = &tokens[1..];
+    }
+    Ok((new_invalid(source, Location { file, start, end }), tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_tokenizer::tokens::{mock_location, Token};
+
+    use crate::cst::{matches_kind, new_invalid};
+
+    #[test]
+    fn test_invalid_parse_until_newline() -> Result<()> {
+        let tokens = vec![
+            Token::AngleBracketClose(mock_location()),
+            Token::Text(" b".to_string(), mock_location()),
+            Token::NewLine(mock_location()),
+            Token::EOF(mock_location()),
+        ];
+        let (cst, tokens) = super::parse(&tokens)?;
+        assert_eq!(tokens.len(), 2);
+        assert!(matches_kind(
+            &cst,
+            &new_invalid("> b".to_string(), mock_location())
+        ));
+        assert_eq!(cst.value(), Some("> b".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_parse_failure() {
+        let tokens = vec![Token::NewLine(mock_location())];
+        assert!(super::parse(&tokens).is_err());
+
+        let tokens = vec![Token::EOF(mock_location())];
+        assert!(super::parse(&tokens).is_err());
+    }
+}
@@ -2,18 +2,26 @@ mod angle;
 mod bracket;
 mod bracket_close;
 mod comma;
+mod comment;
 pub mod cst;
 mod curly;
 mod dot;
 mod eof;
+mod equal;
+pub mod error;
 mod escaped;
 mod expr;
 mod expr_or_close;
+pub mod format;
+pub mod id;
 mod ident;
+mod invalid;
 mod modules;
 mod newline;
 pub mod parse;
 mod parser;
+pub mod print;
+mod raw;
 mod square;
 mod stmt;
 mod text;
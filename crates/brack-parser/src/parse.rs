@@ -1,15 +1,55 @@
-use anyhow::Result;
-use brack_tokenizer::tokens::Token;
+use brack_tokenizer::tokens::{mock_location, Token};
 
 use crate::{
-    cst::{new_document, CST},
-    eof, newline, stmt,
+    cst::{new_front_matter, InnerNode, CST},
+    eof,
+    error::ParseError,
+    invalid, newline, stmt,
 };
 
-// (stmt newline newline+)* stmt? newline* EOF
-pub fn parse(tokens: &[Token]) -> Result<CST> {
+// front_matter? (newline* (stmt newline newline+)* stmt? newline* invalid?)* EOF
+// The front matter is kept in the CST so that the source can be printed back, but is not parsed.
+// Lines that do not match the grammar are wrapped in `invalid` and reported as errors,
+// so that a CST is always produced.
+pub fn parse(tokens: &[Token]) -> (CST, Vec<ParseError>) {
     let mut tokens = tokens;
-    let mut cst = new_document();
+    let mut document = InnerNode::new(mock_location());
+    let mut errors = vec![];
+
+    if let Some(Token::FrontMatter(source, location)) = tokens.first() {
+        document.add(new_front_matter(source.clone(), location.clone()));
+        tokens = &tokens[1..];
+    }
+
+    loop {
+        tokens = parse_stmts(tokens, &mut document);
+
+        if let Ok((cst1, _)) = eof::parse(tokens) {
+            document.add(cst1);
+            break;
+        }
+
+        match invalid::parse(tokens) {
+            Ok((cst1, new_tokens)) => {
+                errors.push(ParseError::UnexpectedToken(cst1.location()));
+                document.add(cst1);
+                tokens = new_tokens;
+            }
+            Err(_) => break,
+        }
+    }
+
+    (CST::Document(document), errors)
+}
+
+// newline* (stmt newline newline+)* stmt? newline*
+fn parse_stmts<'a>(tokens: &'a [Token], document: &mut InnerNode) -> &'a [Token] {
+    let mut tokens = tokens;
+
+    while let Ok((cst1, new_tokens)) = newline::parse(tokens) {
+        document.add(cst1);
+        tokens = new_tokens;
+    }
 
     loop {
         let mut csts = vec![];
@@ -36,22 +76,57 @@ pub fn parse(tokens: &[Token]) -> Result<CST> {
 
         tokens = tokens1;
         for cst1 in csts {
-            cst.add(cst1);
+            document.add(cst1);
         }
     }
 
     if let Ok((cst1, new_tokens)) = stmt::parse(tokens) {
-        cst.add(cst1);
+        document.add(cst1);
         tokens = new_tokens;
     }
 
     while let Ok((cst1, new_tokens)) = newline::parse(tokens) {
-        cst.add(cst1);
+        document.add(cst1);
         tokens = new_tokens;
     }
 
-    let (cst1, _) = eof::parse(tokens)?;
-    cst.add(cst1);
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_tokenizer::tokenize::tokenize_str;
+
+    use crate::{cst::CST, error::ParseError, print::print};
+
+    #[test]
+    fn test_parse_recovers_from_stray_bracket() -> Result<()> {
+        let text = "a > b\nc\n\nd";
+        let tokens = tokenize_str(text)?;
+        let (cst, errors) = super::parse(&tokens);
+        assert_eq!(errors.len(), 1);
+        let ParseError::UnexpectedToken(location) = &errors[0];
+        assert_eq!((location.start.line, location.start.character), (0, 2));
+        assert_eq!((location.end.line, location.end.character), (0, 5));
 
-    Ok(cst)
+        let invalids = cst
+            .try_children()?
+            .iter()
+            .filter(|child| matches!(child, CST::Invalid(_)))
+            .collect::<Vec<_>>();
+        assert_eq!(invalids.len(), 1);
+        assert_eq!(invalids[0].value(), Some("> b".to_string()));
+        assert!(matches!(cst.try_children()?.last(), Some(CST::EOF(_))));
+        assert_eq!(print(&cst), text);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_without_errors() -> Result<()> {
+        let tokens = tokenize_str("a\n\n[std.* b]\n")?;
+        let (_, errors) = super::parse(&tokens);
+        assert!(errors.is_empty());
+        Ok(())
+    }
 }
@@ -0,0 +1,104 @@
+use crate::cst::CST;
+
+/// Turns a CST back into the source text it was parsed from.
+pub fn print(cst: &CST) -> String {
+    let mut source = String::new();
+    print_to(cst, &mut source);
+    source
+}
+
+fn print_to(cst: &CST, source: &mut String) {
+    match cst {
+        CST::Document(node)
+        | CST::Stmt(node)
+        | CST::Expr(node)
+        | CST::Angle(node)
+        | CST::Curly(node)
+        | CST::Square(node) => {
+            for child in &node.children {
+                print_to(child, source);
+            }
+        }
+        CST::BackSlash(node) => {
+            source.push('\\');
+            for child in &node.children {
+                print_to(child, source);
+            }
+        }
+        CST::AngleBracketOpen(_) => source.push('<'),
+        CST::AngleBracketClose(_) => source.push('>'),
+        CST::SquareBracketOpen(_) => source.push('['),
+        CST::SquareBracketClose(_) => source.push(']'),
+        CST::CurlyBracketOpen(_) => source.push('{'),
+        CST::CurlyBracketClose(_) => source.push('}'),
+        CST::Comment(leaf) => {
+            source.push_str("//");
+            source.push_str(leaf.value.as_deref().unwrap_or_default());
+        }
+        CST::FrontMatter(leaf)
+        | CST::Module(leaf)
+        | CST::Ident(leaf)
+        | CST::Text(leaf)
+        | CST::Invalid(leaf) => {
+            source.push_str(leaf.value.as_deref().unwrap_or_default());
+        }
+        CST::Raw(raw) => source.push_str(&raw.raw.source()),
+        CST::Whitespace(_) => source.push(' '),
+        CST::Newline(_) => source.push('\n'),
+        CST::Dot(_) => source.push('.'),
+        CST::Comma(_) => source.push(','),
+        CST::Equal(_) => source.push('='),
+        CST::EOF(_) => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::Result;
+    use brack_tokenizer::tokenize::tokenize_str;
+
+    use crate::parse::parse;
+
+    use super::print;
+
+    fn assert_round_trip(text: &str) -> Result<()> {
+        let tokens = tokenize_str(text)?;
+        let (cst, _) = parse(&tokens);
+        assert_eq!(print(&cst), text);
+        Ok(())
+    }
+
+    #[test]
+    fn test_print_round_trip_escaped() -> Result<()> {
+        assert_round_trip("\\<\\>\\{\\}\\[\\]\\\\ \\a\n[std.* a\\, b\\.]\n\\//text")
+    }
+
+    #[test]
+    fn test_print_round_trip_raw_blocks() -> Result<()> {
+        assert_round_trip("```\n```\n\n````rust\n```\n[a.b]\n````\n")
+    }
+
+    #[test]
+    fn test_print_round_trip_front_matter() -> Result<()> {
+        assert_round_trip("+++\ntitle = \"Brack\"\n+++\n[std.* a]\n")?;
+        assert_round_trip("+++\r\ndraft = true\r\n+++\r\n\nHello")?;
+        assert_round_trip("+++\n+++")
+    }
+
+    #[test]
+    fn test_print_round_trip_tokenizer_fixtures() -> Result<()> {
+        let mut paths = fs::read_dir("../brack-tokenizer/test")?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.sort();
+        for path in paths {
+            let text = fs::read_to_string(&path)?;
+            let tokens = tokenize_str(&text)?;
+            let (cst, _) = parse(&tokens);
+            assert_eq!(print(&cst), text, "{}", path.display());
+        }
+        Ok(())
+    }
+}
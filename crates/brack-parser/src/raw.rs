@@ -0,0 +1,46 @@
+use anyhow::{bail, Result};
+use brack_tokenizer::tokens::Token;
+
+use crate::{cst::new_raw, parser::Parser};
+
+// raw
+pub fn parse(tokens: &[Token]) -> Result<Parser> {
+    if let Some(token) = tokens.first() {
+        match token {
+            Token::Raw(raw, location) => {
+                return Ok((new_raw(raw.clone(), location.clone()), &tokens[1..]));
+            }
+            token => bail!("Expected raw block token, found {:?}", token),
+        }
+    }
+    bail!("Expected raw block token, found none");
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_tokenizer::tokens::{mock_location, RawBlock, Token};
+
+    use crate::cst::{matches_kind, new_raw};
+
+    #[test]
+    fn test_raw_parse_only_raw() -> Result<()> {
+        let raw = RawBlock {
+            fence_length: 3,
+            info: Some("rust".to_string()),
+            content: "[a.b]\n".to_string(),
+        };
+        let tokens = vec![Token::Raw(raw.clone(), mock_location())];
+        let (cst, tokens) = super::parse(&tokens)?;
+        assert_eq!(tokens.len(), 0);
+        assert!(matches_kind(&cst, &new_raw(raw, mock_location())));
+        assert_eq!(cst.value(), Some("[a.b]\n".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw_parse_failure() {
+        let tokens = vec![Token::Text("```".to_string(), mock_location())];
+        assert!(super::parse(&tokens).is_err());
+    }
+}
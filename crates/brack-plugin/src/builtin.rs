@@ -0,0 +1,299 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use brack_transformer::{
+    ast::{new_expr, AST},
+    command::CommandCall,
+    unparse::unparse,
+};
+
+use crate::{metadata::Metadata, types::Type, value::Value};
+
+/// The module of the commands provided by the compiler itself.
+/// No plugin can be named after it.
+pub const MODULE_NAME: &str = "brack";
+
+/// The commands of the `brack` module, which are implemented in Rust instead of WebAssembly.
+/// Variables set by `<brack.set ...>` live until the metadata of the next document is set.
+pub struct Builtin {
+    pub signature_to_metadata: HashMap<(String, Type), Metadata>,
+    variables: HashMap<String, AST>,
+}
+
+impl Default for Builtin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Builtin {
+    pub fn new() -> Self {
+        let signatures = vec![
+            ("raw", Type::TInline, vec![("text", Type::TRaw)]),
+            ("raw", Type::TBlock, vec![("text", Type::TRaw)]),
+            ("ref", Type::TInline, vec![("label", Type::TReference)]),
+            (
+                "date",
+                Type::TInline,
+                vec![("format", Type::TOption(Box::new(Type::TInline)))],
+            ),
+            (
+                "meta",
+                Type::TInline,
+                vec![
+                    ("keys", Type::TArray(Box::new(Type::TInline))),
+                    ("metadata", Type::TMetadata),
+                ],
+            ),
+            (
+                "set",
+                Type::TAST,
+                vec![("name", Type::TInline), ("value", Type::TInline)],
+            ),
+            ("get", Type::TAST, vec![("name", Type::TInline)]),
+            ("include", Type::TAST, vec![("path", Type::TInline)]),
+            (
+                "define",
+                Type::TAST,
+                vec![
+                    ("module", Type::TInline),
+                    ("ident", Type::TInline),
+                    ("params", Type::TArray(Box::new(Type::TInline))),
+                ],
+            ),
+        ];
+        let signature_to_metadata = signatures
+            .into_iter()
+            .map(|(name, return_type, argument_types)| {
+                let metadata = Metadata {
+                    command_name: name.to_string(),
+                    call_name: name.to_string(),
+                    argument_types: argument_types
+                        .into_iter()
+                        .map(|(name, t)| (name.to_string(), t))
+                        .collect(),
+                    return_type: return_type.clone(),
+                    counter: None,
+                    label: None,
+                };
+                ((name.to_string(), return_type), metadata)
+            })
+            .collect();
+        Self {
+            signature_to_metadata,
+            variables: HashMap::new(),
+        }
+    }
+
+    pub fn command_metadata(&self, command_name: &str, typ: Type) -> Result<&Metadata> {
+        self.signature_to_metadata
+            .get(&(command_name.to_string(), typ))
+            .ok_or_else(|| anyhow::anyhow!("command not found: {}.{}", MODULE_NAME, command_name))
+    }
+
+    pub fn clear_variables(&mut self) {
+        self.variables.clear();
+    }
+
+    pub fn call_inline_command(&self, command_name: &str, args: Vec<Value>) -> Result<String> {
+        match (command_name, &args[..]) {
+            ("raw", [Value::Raw(_, content)]) => Ok(content.clone()),
+            ("date", [Value::TextOption(format)]) => {
+                format_date(today()?, format.as_deref().unwrap_or("%Y-%m-%d"))
+            }
+            ("meta", [Value::TextArray(keys), Value::Metadata(metadata)]) => meta(metadata, keys),
+            ("ref", [Value::Reference(target)]) => target
+                .number
+                .map(|number| number.to_string())
+                .ok_or_else(|| anyhow::anyhow!("`{}` has no number", target.label)),
+            _ => anyhow::bail!("command not found: {}.{}", MODULE_NAME, command_name),
+        }
+    }
+
+    pub fn call_block_command(&self, command_name: &str, args: Vec<Value>) -> Result<String> {
+        match (command_name, &args[..]) {
+            ("raw", [Value::Raw(_, content)]) => Ok(content.clone()),
+            _ => anyhow::bail!("command not found: {}.{}", MODULE_NAME, command_name),
+        }
+    }
+
+    pub fn call_macro_command(&mut self, call: CommandCall) -> Result<AST> {
+        let mut args = call.args.into_iter().map(|arg| arg.value);
+        match (call.ident.as_str(), args.len()) {
+            ("set", 2) => {
+                let name = unparse(&args.next().unwrap()).trim().to_string();
+                self.variables.insert(name, args.next().unwrap());
+                Ok(new_expr(vec![], call.location))
+            }
+            ("get", 1) => {
+                let name = unparse(&args.next().unwrap()).trim().to_string();
+                self.variables
+                    .get(&name)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("variable `{}` is not set", name))
+            }
+            ("include", _) => {
+                anyhow::bail!("brack.include must be on its own line in a file of the document")
+            }
+            ("define", _) => anyhow::bail!("brack.define is handled by the macro expander"),
+            ("set", _) => anyhow::bail!("brack.set takes a name and a value"),
+            ("get", _) => anyhow::bail!("brack.get takes exactly one name"),
+            (ident, _) => anyhow::bail!("command not found: {}.{}", MODULE_NAME, ident),
+        }
+    }
+}
+
+/// Looks up a value by a path of keys.
+/// Strings are written as they are and other values as JSON.
+fn meta(metadata: &serde_json::Value, keys: &[String]) -> Result<String> {
+    if keys.is_empty() {
+        anyhow::bail!("brack.meta requires at least one key");
+    }
+    let value = keys
+        .iter()
+        .try_fold(metadata, |value, key| value.get(key.trim()))
+        .ok_or_else(|| anyhow::anyhow!("metadata has no key `{}`", keys.join(".")))?;
+    Ok(match value {
+        serde_json::Value::String(string) => string.clone(),
+        value => value.to_string(),
+    })
+}
+
+/// The current date in UTC, or the date of `SOURCE_DATE_EPOCH` for reproducible builds.
+fn today() -> Result<(i64, u32, u32)> {
+    let seconds = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch.trim().parse::<i64>()?,
+        Err(_) => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64,
+    };
+    Ok(civil_from_days(seconds.div_euclid(86400)))
+}
+
+/// Converts days since 1970-01-01 to a date in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Supports `%Y`, `%m`, `%d` and `%%`.
+fn format_date((year, month, day): (i64, u32, u32), format: &str) -> Result<String> {
+    let mut result = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Y') => result.push_str(&format!("{:04}", year)),
+            Some('m') => result.push_str(&format!("{:02}", month)),
+            Some('d') => result.push_str(&format!("{:02}", day)),
+            Some('%') => result.push('%'),
+            Some(c) => anyhow::bail!("brack.date does not support `%{}`", c),
+            None => anyhow::bail!("brack.date format ends with `%`"),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_tokenizer::tokens::mock_location;
+    use brack_transformer::{
+        ast::{new_angle, new_expr, new_ident, new_module, new_text, AST},
+        command::CommandCall,
+        unparse::unparse,
+    };
+    use serde_json::json;
+
+    use crate::value::Value;
+
+    use super::{civil_from_days, format_date, Builtin};
+
+    fn call(ident: &str, args: &[&str]) -> Result<CommandCall> {
+        let mut children = vec![
+            new_module(Some("brack".to_string()), mock_location()),
+            new_ident(Some(ident.to_string()), mock_location()),
+        ];
+        children.extend(args.iter().map(|arg| {
+            new_expr(
+                vec![new_text(Some(arg.to_string()), mock_location())],
+                mock_location(),
+            )
+        }));
+        CommandCall::resolve(&new_angle(children, mock_location()))
+    }
+
+    #[test]
+    fn test_inline_commands() -> Result<()> {
+        let builtin = Builtin::new();
+        let raw = builtin.call_inline_command("raw", vec![Value::Raw(None, "<br>".to_string())])?;
+        assert_eq!(raw, "<br>");
+        let metadata = json!({ "title": "Brack", "author": { "name": "A", "age": 3 } });
+        let title = builtin.call_inline_command(
+            "meta",
+            vec![
+                Value::TextArray(vec!["title".to_string()]),
+                Value::Metadata(metadata.clone()),
+            ],
+        )?;
+        assert_eq!(title, "Brack");
+        let age = builtin.call_inline_command(
+            "meta",
+            vec![
+                Value::TextArray(vec!["author".to_string(), "age".to_string()]),
+                Value::Metadata(metadata.clone()),
+            ],
+        )?;
+        assert_eq!(age, "3");
+        assert!(builtin
+            .call_inline_command(
+                "meta",
+                vec![
+                    Value::TextArray(vec!["date".to_string()]),
+                    Value::Metadata(metadata),
+                ],
+            )
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_date() -> Result<()> {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_999), (2024, 10, 3));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(
+            format_date((2024, 3, 9), "%Y/%m/%d 100%%")?,
+            "2024/03/09 100%"
+        );
+        assert!(format_date((2024, 3, 9), "%H").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_and_get_variables() -> Result<()> {
+        let mut builtin = Builtin::new();
+        let set = builtin.call_macro_command(call("set", &["title", "Brack"])?)?;
+        assert!(matches!(set, AST::Expr(_)));
+        assert_eq!(unparse(&set), "");
+        let value = builtin.call_macro_command(call("get", &["title"])?)?;
+        assert_eq!(unparse(&value), "Brack");
+        builtin.clear_variables();
+        assert!(builtin
+            .call_macro_command(call("get", &["title"])?)
+            .is_err());
+        Ok(())
+    }
+}
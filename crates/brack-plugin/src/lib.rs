@@ -1,6 +1,9 @@
+pub mod builtin;
 pub mod feature_flag;
+pub mod macro_input;
 pub mod metadata;
 pub mod plugin;
 pub mod plugins;
+pub mod references;
 pub mod types;
 pub mod value;
@@ -0,0 +1,22 @@
+use brack_transformer::{ast::AST, command::CommandCall};
+use serde::{Deserialize, Serialize};
+
+/// What a macro receives instead of the whole document.
+/// The macro returns the subtree replacing the call, which the expander splices into the document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroInput {
+    /// The macro call with its arguments.
+    pub call: CommandCall,
+    pub context: MacroContext,
+}
+
+/// Read-only information about the document around a macro call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroContext {
+    /// The metadata of the document, which is also passed to commands taking `TMetadata`.
+    pub metadata: serde_json::Value,
+    /// The number of macros in whose results this call is nested.
+    pub depth: usize,
+    /// The document before the expansion, sent only to macros which take a parameter of `TAST`.
+    pub document: Option<AST>,
+}
@@ -8,4 +8,10 @@ pub struct Metadata {
     pub call_name: String,
     pub argument_types: Vec<(String, Type)>,
     pub return_type: Type,
+    /// The counter incremented by every call of the command, whose value is the number of the call.
+    #[serde(default)]
+    pub counter: Option<String>,
+    /// The parameter whose argument is a label naming the call, which references can look up.
+    #[serde(default)]
+    pub label: Option<String>,
 }
@@ -6,6 +6,7 @@ use std::{
 
 use crate::{feature_flag::FeatureFlag, metadata::Metadata, types::Type};
 use anyhow::Result;
+use brack_tokenizer::diagnostic::Diagnostic;
 use extism::{FromBytes, Plugin as ExtismPlugin, ToBytes};
 use extism_convert::Json;
 
@@ -96,7 +97,14 @@ impl Plugin {
             .ok_or_else(|| anyhow::anyhow!("metadata not found: {}", command_name))?;
         let result = self
             .extism_plugin
-            .call::<T, U>(metadata.call_name.clone(), args)?;
+            .call::<T, U>(metadata.call_name.clone(), args)
+            // Plugins report a diagnostic by returning it as a JSON error.
+            .map_err(
+                |error| match serde_json::from_str::<Diagnostic>(&error.to_string()) {
+                    Ok(diagnostic) => anyhow::Error::new(diagnostic),
+                    Err(_) => error,
+                },
+            )?;
         Ok(result)
     }
 }
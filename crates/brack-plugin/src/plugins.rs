@@ -1,18 +1,30 @@
 use std::collections::HashMap;
 
 use anyhow::Result;
-use brack_transformer::ast::AST;
+use brack_transformer::{ast::AST, command::CommandCall};
 use extism::{FromBytes, ToBytes};
 use extism_convert::Json;
 
-use crate::{plugin::Plugin, types::Type, value::Value};
+use crate::{
+    builtin::{self, Builtin},
+    macro_input::{MacroContext, MacroInput},
+    metadata::Metadata,
+    plugin::Plugin,
+    references::References,
+    types::Type,
+    value::Value,
+};
 
 pub struct Plugins {
     pub name_to_plugin: HashMap<String, Plugin>,
+    /// The `brack` module, which is looked up before the plugins.
+    pub builtin: Builtin,
     document_hook_plugin_name: Option<String>,
     stmt_hook_plugin_name: Option<String>,
     expr_hook_plugin_name: Option<String>,
     text_hook_plugin_name: Option<String>,
+    metadata: serde_json::Value,
+    references: References,
 }
 
 impl Plugins {
@@ -25,6 +37,12 @@ impl Plugins {
 
         for plugin in plugins {
             let name = plugin.name.clone();
+            if name == builtin::MODULE_NAME {
+                return Err(anyhow::anyhow!(
+                    "the module name `{}` is reserved for the built-in commands",
+                    name
+                ));
+            }
             if plugin.feature_flag.document_hook {
                 if document_hook_plugin_name.is_some() {
                     return Err(anyhow::anyhow!("only one document hook is allowed"));
@@ -54,27 +72,81 @@ impl Plugins {
 
         Ok(Self {
             name_to_plugin,
+            builtin: Builtin::new(),
             document_hook_plugin_name,
             stmt_hook_plugin_name,
             expr_hook_plugin_name,
             text_hook_plugin_name,
+            metadata: serde_json::Value::Object(Default::default()),
+            references: References::default(),
         })
     }
 
-    pub fn argument_types(
+    /// Sets the metadata of the document being compiled,
+    /// which is passed to commands and hooks taking `TMetadata`.
+    /// The variables of the previous document are cleared as well.
+    pub fn set_metadata(&mut self, metadata: serde_json::Value) {
+        self.metadata = metadata;
+        self.builtin.clear_variables();
+    }
+
+    pub fn metadata(&self) -> &serde_json::Value {
+        &self.metadata
+    }
+
+    /// Sets the labels and numbers collected from the document being generated.
+    pub fn set_references(&mut self, references: References) {
+        self.references = references;
+    }
+
+    pub fn references(&self) -> &References {
+        &self.references
+    }
+
+    /// Appends the metadata to the arguments of a hook if the hook takes it.
+    fn hook_args(
+        &self,
+        plugin_name: &str,
+        hook_name: &str,
+        typ: Type,
+        args: Vec<Value>,
+    ) -> Vec<Value> {
+        let takes_metadata = self
+            .argument_types(plugin_name, hook_name, typ)
+            .is_ok_and(|arg_types| arg_types.iter().any(|(_, t)| *t == Type::TMetadata));
+        if takes_metadata {
+            [args, vec![Value::Metadata(self.metadata.clone())]].concat()
+        } else {
+            args
+        }
+    }
+
+    pub fn command_metadata(
         &self,
         module_name: &str,
         command_name: &str,
         typ: Type,
-    ) -> Result<Vec<(String, Type)>> {
+    ) -> Result<&Metadata> {
+        if module_name == builtin::MODULE_NAME {
+            return self.builtin.command_metadata(command_name, typ);
+        }
         let plugin = self
             .name_to_plugin
             .get(module_name)
             .ok_or_else(|| anyhow::anyhow!("plugin not found: {}", module_name))?;
-        let metadata = plugin
+        plugin
             .signature_to_metadata
             .get(&(command_name.to_string(), typ))
-            .ok_or_else(|| anyhow::anyhow!("command not found: {}", command_name))?;
+            .ok_or_else(|| anyhow::anyhow!("command not found: {}", command_name))
+    }
+
+    pub fn argument_types(
+        &self,
+        module_name: &str,
+        command_name: &str,
+        typ: Type,
+    ) -> Result<Vec<(String, Type)>> {
+        let metadata = self.command_metadata(module_name, command_name, typ)?;
         Ok(metadata.argument_types.clone())
     }
 
@@ -99,6 +171,9 @@ impl Plugins {
         command_name: &str,
         args: Vec<Value>,
     ) -> Result<String> {
+        if plugin_name == builtin::MODULE_NAME {
+            return self.builtin.call_inline_command(command_name, args);
+        }
         let result = self.call::<Json<Vec<Value>>, String>(
             plugin_name,
             command_name,
@@ -114,6 +189,9 @@ impl Plugins {
         command_name: &str,
         args: Vec<Value>,
     ) -> Result<String> {
+        if plugin_name == builtin::MODULE_NAME {
+            return self.builtin.call_block_command(command_name, args);
+        }
         let result = self.call::<Json<Vec<Value>>, String>(
             plugin_name,
             command_name,
@@ -123,18 +201,35 @@ impl Plugins {
         Ok(result)
     }
 
+    /// Calls a macro with the call itself and returns the subtree replacing it.
+    /// `document` is cloned only for the macros which take a parameter of `TAST`.
     pub fn call_macro_command(
         &mut self,
-        plugin_name: &str,
-        command_name: &str,
-        ast: AST,
-        id: String,
+        call: CommandCall,
+        document: &AST,
+        depth: usize,
     ) -> Result<AST> {
-        let result = self.call::<Json<(AST, String)>, Json<AST>>(
-            plugin_name,
-            command_name,
+        if call.module == builtin::MODULE_NAME {
+            return self.builtin.call_macro_command(call);
+        }
+        let plugin_name = call.module.clone();
+        let command_name = call.ident.clone();
+        let takes_document = self
+            .argument_types(&plugin_name, &command_name, Type::TAST)
+            .is_ok_and(|arg_types| arg_types.iter().any(|(_, t)| *t == Type::TAST));
+        let input = MacroInput {
+            call,
+            context: MacroContext {
+                metadata: self.metadata.clone(),
+                depth,
+                document: takes_document.then(|| document.clone()),
+            },
+        };
+        let result = self.call::<Json<MacroInput>, Json<AST>>(
+            &plugin_name,
+            &command_name,
             Type::TAST,
-            Json((ast, id)),
+            Json(input),
         )?;
         let Json(ast) = result;
         Ok(ast)
@@ -143,6 +238,7 @@ impl Plugins {
     pub fn call_document_hook(&mut self, args: Vec<Value>) -> Result<Option<String>> {
         let document_hook_plugin_name = self.document_hook_plugin_name.clone();
         if let Some(plugin_name) = document_hook_plugin_name {
+            let args = self.hook_args(&plugin_name, "document", Type::TBlock, args);
             let result = self.call_block_command(&plugin_name, "document", args)?;
             return Ok(Some(result));
         }
@@ -152,6 +248,7 @@ impl Plugins {
     pub fn call_stmt_hook(&mut self, args: Vec<Value>) -> Result<Option<String>> {
         let stmt_hook_plugin_name = self.stmt_hook_plugin_name.clone();
         if let Some(plugin_name) = stmt_hook_plugin_name {
+            let args = self.hook_args(&plugin_name, "stmt", Type::TBlock, args);
             let result = self.call_block_command(&plugin_name, "stmt", args)?;
             return Ok(Some(result));
         }
@@ -161,6 +258,7 @@ impl Plugins {
     pub fn call_expr_hook(&mut self, args: Vec<Value>) -> Result<Option<String>> {
         let expr_hook_plugin_name = self.expr_hook_plugin_name.clone();
         if let Some(plugin_name) = expr_hook_plugin_name {
+            let args = self.hook_args(&plugin_name, "expr", Type::TInline, args);
             let result = self.call_inline_command(&plugin_name, "expr", args)?;
             return Ok(Some(result));
         }
@@ -170,6 +268,7 @@ impl Plugins {
     pub fn call_text_hook(&mut self, args: Vec<Value>) -> Result<Option<String>> {
         let text_hook_plugin_name = self.text_hook_plugin_name.clone();
         if let Some(plugin_name) = text_hook_plugin_name {
+            let args = self.hook_args(&plugin_name, "text", Type::TInline, args);
             let result = self.call_inline_command(&plugin_name, "text", args)?;
             return Ok(Some(result));
         }
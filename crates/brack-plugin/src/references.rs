@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use brack_tokenizer::{diagnostic::Diagnostic, tokens::Location};
+use serde::{Deserialize, Serialize};
+
+/// A command call named by a label.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Target {
+    pub label: String,
+    /// The id of the command node, which is unique in the document.
+    pub id: String,
+    /// The counter of the command and the number of the call, if the command has a counter.
+    pub counter: Option<String>,
+    pub number: Option<usize>,
+}
+
+/// The labels and numbers collected from the whole document before any command is called.
+#[derive(Debug, Clone, Default)]
+pub struct References {
+    counters: HashMap<String, usize>,
+    /// The numbers of the calls by the ids of their nodes.
+    numbers: HashMap<String, usize>,
+    labels: HashMap<String, (Target, Location)>,
+}
+
+impl References {
+    /// Increments `counter` and gives its value to the call `id`.
+    pub fn count(&mut self, counter: &str, id: &str) -> usize {
+        let number = self.counters.entry(counter.to_string()).or_default();
+        *number += 1;
+        self.numbers.insert(id.to_string(), *number);
+        *number
+    }
+
+    pub fn number(&self, id: &str) -> Option<usize> {
+        self.numbers.get(id).copied()
+    }
+
+    /// Registers a label, which must not be defined twice in a document.
+    /// `location` is the location of the label.
+    pub fn define(&mut self, target: Target, location: Location) -> Result<()> {
+        if let Some((_, previous)) = self.labels.get(&target.label) {
+            anyhow::bail!(Diagnostic::error(
+                "B0040",
+                format!("The label `{}` is defined more than once", target.label),
+                location,
+            )
+            .with_label(previous.clone(), "first defined here"));
+        }
+        self.labels.insert(target.label.clone(), (target, location));
+        Ok(())
+    }
+
+    /// `location` is the location of the reference.
+    pub fn resolve(&self, label: &str, location: &Location) -> Result<Target> {
+        match self.labels.get(label) {
+            Some((target, _)) => Ok(target.clone()),
+            None => {
+                let mut labels = self
+                    .labels
+                    .keys()
+                    .map(|label| format!("`{}`", label))
+                    .collect::<Vec<_>>();
+                labels.sort();
+                let help = if labels.is_empty() {
+                    "no label is defined in the document".to_string()
+                } else {
+                    format!("the labels are {}", labels.join(", "))
+                };
+                anyhow::bail!(Diagnostic::error(
+                    "B0041",
+                    format!("The label `{}` is not defined", label),
+                    location.clone(),
+                )
+                .with_help(help))
+            }
+        }
+    }
+}
@@ -5,28 +5,44 @@ pub enum Type {
     TInline,
     TOption(Box<Type>),
     TBlock,
+    /// Takes a raw block as it is, together with its info string.
+    TRaw,
     TArray(Box<Type>),
     TInlineCmd(String),
     TBlockCmd(String),
     TAST,
+    /// Receives the metadata of the document, which is not passed by the caller.
+    TMetadata,
+    /// Takes a label and receives the call which defines it.
+    TReference,
+    /// Receives the number of the call from the counter of the command, which is not passed by the caller.
+    TNumber,
+}
+
+impl Type {
+    /// Whether the parameter is bound by the compiler instead of the caller.
+    pub fn is_implicit(&self) -> bool {
+        matches!(self, Type::TMetadata | Type::TNumber)
+    }
 }
 
 pub fn arg_counter(arg_types: &Vec<Type>) -> (usize, usize) {
     let mut min = 0;
-    let mut max = 0;
+    let mut max: usize = 0;
     for arg_type in arg_types {
         match arg_type {
             Type::TOption(_) => {
                 min += 0;
-                max += 1;
+                max = max.saturating_add(1);
             }
             Type::TArray(_) => {
                 min += 0;
                 max = usize::MAX;
             }
+            Type::TMetadata | Type::TNumber => (),
             _ => {
                 min += 1;
-                max += 1;
+                max = max.saturating_add(1);
             }
         }
     }
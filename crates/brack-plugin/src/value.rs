@@ -1,8 +1,18 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::references::Target;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum Value {
     Text(String),
     TextArray(Vec<String>),
     TextOption(Option<String>),
+    /// The info string and the content of a raw block.
+    Raw(Option<String>, String),
+    /// The metadata of the document, merged from `Brack.toml` and the front matter.
+    Metadata(serde_json::Value),
+    /// The call defining the label given to a parameter of `TReference`.
+    Reference(Target),
+    /// The number of the call for a parameter of `TNumber`.
+    Number(usize),
 }
@@ -1,3 +1,5 @@
+use brack_expander::expand::DEFAULT_MAX_DEPTH;
+use brack_transformer::id::IdStrategy;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -6,6 +8,19 @@ pub struct Document {
     pub version: String,
     pub backend: String,
     pub authors: Vec<String>,
+    /// `deterministic` (default) or `random`.
+    #[serde(default)]
+    pub ids: IdStrategy,
+    /// The limit of macro expansions nested in the results of other macros.
+    #[serde(default = "default_macro_depth")]
+    pub macro_depth: usize,
+    /// Other keys such as `title`, which are passed to plugins as the metadata of the documents.
+    #[serde(flatten)]
+    pub extra: toml::Table,
+}
+
+fn default_macro_depth() -> usize {
+    DEFAULT_MAX_DEPTH
 }
 
 impl Default for Document {
@@ -15,6 +30,9 @@ impl Default for Document {
             version: "0.1.0".to_string(),
             backend: "".to_string(),
             authors: vec!["your name <your email>".to_string()],
+            ids: IdStrategy::default(),
+            macro_depth: DEFAULT_MAX_DEPTH,
+            extra: toml::Table::new(),
         }
     }
 }
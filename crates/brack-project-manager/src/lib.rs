@@ -1,4 +1,5 @@
 pub mod config;
 pub mod document;
+pub mod metadata;
 pub mod plugin;
 pub mod project;
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use brack_tokenizer::{diagnostic::Diagnostic, front_matter::FrontMatter};
+
+use crate::document::Document;
+
+/// Builds the metadata of a document, which is passed to plugins.
+/// The keys of the front matter take precedence over the ones of `[document]` in `Brack.toml`.
+pub fn document_metadata(
+    document: Option<&Document>,
+    front_matter: Option<&FrontMatter>,
+) -> Result<serde_json::Value> {
+    let mut table = match document.map(toml::Value::try_from).transpose()? {
+        Some(toml::Value::Table(table)) => table,
+        _ => toml::Table::new(),
+    };
+    if let Some(front_matter) = front_matter {
+        let front_matter_table = front_matter
+            .content
+            .parse::<toml::Table>()
+            .map_err(|error| {
+                Diagnostic::error(
+                    "B0035",
+                    format!("Invalid front matter: {}", error.message()),
+                    front_matter.location.clone(),
+                )
+                .with_help("the front matter between the `+++` lines is written in TOML")
+            })?;
+        table.extend(front_matter_table);
+    }
+    Ok(to_json(toml::Value::Table(table)))
+}
+
+/// Dates and times are converted to strings in RFC 3339.
+fn to_json(value: toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(string) => string.into(),
+        toml::Value::Integer(integer) => integer.into(),
+        toml::Value::Float(float) => float.into(),
+        toml::Value::Boolean(boolean) => boolean.into(),
+        toml::Value::Datetime(datetime) => datetime.to_string().into(),
+        toml::Value::Array(array) => array.into_iter().map(to_json).collect(),
+        toml::Value::Table(table) => table
+            .into_iter()
+            .map(|(key, value)| (key, to_json(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_tokenizer::{diagnostic::Diagnostic, front_matter::front_matter, source_map::FileId};
+    use serde_json::json;
+
+    use crate::document::Document;
+
+    use super::document_metadata;
+
+    #[test]
+    fn test_front_matter_overrides_document() -> Result<()> {
+        let document = Document {
+            name: "handbook".to_string(),
+            extra: toml::toml! { title = "Handbook" lang = "en" },
+            ..Document::default()
+        };
+        let text =
+            "+++\ntitle = \"Intro\"\ndate = 2024-10-16\ntags = [\"a\", \"b\"]\ndraft = true\n+++\n";
+        let front_matter = front_matter(text, FileId::default());
+        let metadata = document_metadata(Some(&document), front_matter.as_ref())?;
+        assert_eq!(metadata["name"], json!("handbook"));
+        assert_eq!(metadata["lang"], json!("en"));
+        assert_eq!(metadata["title"], json!("Intro"));
+        assert_eq!(metadata["date"], json!("2024-10-16"));
+        assert_eq!(metadata["tags"], json!(["a", "b"]));
+        assert_eq!(metadata["draft"], json!(true));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_front_matter() {
+        let front_matter = front_matter("+++\ntitle =\n+++\n", FileId::default());
+        let error = document_metadata(None, front_matter.as_ref()).unwrap_err();
+        let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+        assert_eq!(diagnostic.code, "B0035");
+    }
+}
@@ -1,7 +1,17 @@
 use crate::config::Config;
+use crate::metadata::document_metadata;
 use crate::plugin::PluginSchema;
 use anyhow::Result;
+use brack_expander::include::resolve_includes;
+use brack_parser::id::id_path;
 use brack_plugin::{feature_flag::FeatureFlag, plugin::Plugin, plugins::Plugins};
+use brack_tokenizer::{
+    diagnostic::{render_error, stderr_supports_color, Diagnostic},
+    front_matter::front_matter,
+    source_map::SourceMap,
+    tokenize::tokenize_file,
+};
+use brack_transformer::id::assign_ids;
 use bytes::Bytes;
 use futures::future::join_all;
 use reqwest;
@@ -11,6 +21,33 @@ use std::{
 };
 use tokio::task::{self, JoinHandle};
 
+fn error_message(source_map: &SourceMap, path: &Path, diagnostics: &[Diagnostic]) -> String {
+    let color = stderr_supports_color();
+    let messages = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.render_with_color(source_map, color))
+        .collect::<Vec<_>>();
+    format!(
+        "Failed to compile {}.\n{}",
+        path.display(),
+        messages.join("\n\n")
+    )
+}
+
+/// Collects the documents in `dir` and its subdirectories,
+/// such as chapters that are pulled in with `brack.include`.
+fn documents_in(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            documents_in(&path, paths)?;
+        } else if path.extension() == Some("[]".as_ref()) {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct Project {
     pub config: Config,
@@ -123,7 +160,10 @@ impl Project {
             plugin_vec.push(Plugin::new(&name, path, feature_flag)?);
         }
         let mut plugins = Plugins::new(plugin_vec)?;
+        let mut source_map = SourceMap::new();
 
+        // Every document is compiled even if some of them fail, so that all errors are reported at once.
+        let mut failed = 0;
         let entries = std::fs::read_dir("docs")?;
         for entry in entries {
             let entry = entry?;
@@ -134,18 +174,24 @@ impl Project {
                 .to_str()
                 .ok_or_else(|| anyhow::anyhow!("Could not convert file name to string."))?;
             if path.extension() == Some("[]".as_ref()) {
-                let tokenized = brack_tokenizer::tokenize::tokenize(path.to_str().unwrap())?;
-                let parsed = brack_parser::parse::parse(&tokenized)?;
-                let (ast, _errors) = brack_transformer::transform::transform(&parsed);
-                let expanded = brack_expander::expand::expander(&ast, &mut plugins)?;
-                let gen = brack_codegen::generate::generate(&expanded, &mut plugins)?;
-                std::fs::create_dir_all("out")?;
-                std::fs::write(
-                    format!("out/{}.{}", file_stem, self.config.document.backend),
-                    gen,
-                )?;
+                match self.compile(&path, &mut source_map, &mut plugins) {
+                    Ok(gen) => {
+                        std::fs::create_dir_all("out")?;
+                        std::fs::write(
+                            format!("out/{}.{}", file_stem, self.config.document.backend),
+                            gen,
+                        )?;
+                    }
+                    Err(error) => {
+                        eprintln!("{}\n", error);
+                        failed += 1;
+                    }
+                }
             }
         }
+        if failed > 0 {
+            anyhow::bail!("{} file(s) failed to compile.", failed);
+        }
 
         println!("Build succeeded.");
         for out in std::fs::read_dir("out")? {
@@ -160,4 +206,124 @@ impl Project {
         }
         Ok(())
     }
+
+    fn compile(
+        &self,
+        path: &Path,
+        source_map: &mut SourceMap,
+        plugins: &mut Plugins,
+    ) -> Result<String> {
+        let file = source_map.load(path)?;
+        let tokenized = tokenize_file(source_map, file)?;
+        let (parsed, parse_errors) = brack_parser::parse::parse(&tokenized);
+        let (ast, errors) = brack_transformer::transform::transform(&parsed);
+        let (mut ast, include_errors) = resolve_includes(ast, file, source_map);
+        // Errors of the parser, the transformer and included files are reported together.
+        let diagnostics = parse_errors
+            .iter()
+            .map(|error| error.to_diagnostic())
+            .chain(errors.iter().map(|error| error.to_diagnostic()))
+            .chain(include_errors)
+            .collect::<Vec<_>>();
+        if !diagnostics.is_empty() {
+            anyhow::bail!(error_message(source_map, path, &diagnostics));
+        }
+        let path_name = id_path(path, &self.root);
+        assign_ids(&mut ast, &path_name, self.config.document.ids);
+        let compile_error = |error: anyhow::Error| {
+            anyhow::anyhow!(
+                "Failed to compile {}.\n{}",
+                path.display(),
+                render_error(&error, source_map, stderr_supports_color())
+            )
+        };
+        let front_matter = source_map
+            .get(file)
+            .and_then(|source| front_matter(&source.text, file));
+        let metadata = document_metadata(Some(&self.config.document), front_matter.as_ref())
+            .map_err(compile_error)?;
+        plugins.set_metadata(metadata);
+        let mut expanded =
+            brack_expander::expand::expand(&ast, plugins, self.config.document.macro_depth)
+                .map_err(compile_error)?;
+        // Nodes created by macros are also given ids.
+        assign_ids(&mut expanded, &path_name, self.config.document.ids);
+        brack_codegen::generate::generate(&expanded, plugins).map_err(compile_error)
+    }
+
+    /// Formats all documents in the `docs` directory and its subdirectories.
+    /// With `check`, files are not rewritten and an error is returned if any of them is not formatted.
+    pub fn fmt(&self, check: bool) -> Result<()> {
+        let mut source_map = SourceMap::new();
+        let mut unformatted = vec![];
+        let mut paths = vec![];
+        documents_in(&self.root.join("docs"), &mut paths)?;
+        for path in paths {
+            let source = std::fs::read_to_string(&path)?;
+            let file = source_map.add(&path, source.clone());
+            let tokenized = tokenize_file(&source_map, file)?;
+            let (parsed, errors) = brack_parser::parse::parse(&tokenized);
+            if !errors.is_empty() {
+                let diagnostics = errors
+                    .iter()
+                    .map(|error| error.to_diagnostic())
+                    .collect::<Vec<_>>();
+                anyhow::bail!(error_message(&source_map, &path, &diagnostics));
+            }
+            let formatted = brack_parser::format::format(&parsed);
+            if formatted == source {
+                continue;
+            }
+            if !check {
+                std::fs::write(&path, formatted)?;
+            }
+            unformatted.push(path);
+        }
+        unformatted.sort();
+
+        if check {
+            if unformatted.is_empty() {
+                return Ok(());
+            }
+            for path in &unformatted {
+                println!("  - {}", path.display());
+            }
+            anyhow::bail!("{} file(s) are not formatted.", unformatted.len());
+        }
+        for path in &unformatted {
+            println!("Formatted {}", path.display());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::Project;
+
+    #[test]
+    fn test_fmt_formats_nested_documents() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("brack-fmt-{}", std::process::id()));
+        let chapters = root.join("docs").join("chapters");
+        std::fs::create_dir_all(&chapters)?;
+        std::fs::write(
+            root.join("docs").join("index.[]"),
+            "[brack.include chapters/one]\n",
+        )?;
+        std::fs::write(chapters.join("one.[]"), "[std.*   a ]")?;
+
+        let project = Project::new(&root);
+        assert!(project.fmt(true).is_err());
+        project.fmt(false)?;
+        assert_eq!(
+            std::fs::read_to_string(chapters.join("one.[]"))?,
+            "[std.* a]\n"
+        );
+        project.fmt(true)?;
+
+        std::fs::remove_dir_all(&root)?;
+        Ok(())
+    }
 }
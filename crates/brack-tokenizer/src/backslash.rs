@@ -1,45 +1,78 @@
-use crate::{
-    dispatch::dispatch,
-    tokenizer::Tokenizer,
-    tokens::{Location, LocationData, Token},
-    utils::separate,
-};
-use anyhow::Result;
+use crate::{tokenizer::Tokenizer, tokens::Token};
 
-pub fn tokenize(t: &Tokenizer) -> Result<Vec<Token>> {
-    let s = t
-        .untreated
-        .clone()
-        .ok_or_else(|| anyhow::anyhow!("`t.untreated` is not set"))?;
-    let (_, tail) = separate(&s);
-    let mut tokens = t
-        .tokens
-        .clone()
-        .ok_or_else(|| anyhow::anyhow!("`t.tokens` is not set"))?;
-    let line = t
-        .line
-        .ok_or_else(|| anyhow::anyhow!("`t.line` is not set"))?;
-    let column = t
-        .column
-        .ok_or_else(|| anyhow::anyhow!("`t.column` is not set"))?;
+pub fn tokenize(t: &mut Tokenizer) {
+    t.push_head(Token::BackSlash(t.head_location()));
+    // An escaped bracket neither opens nor closes a command, so the nest counts are kept as they are.
+    let location = t.head_location();
+    let escaped = match t.peek(0) {
+        // An escaped backslash is consumed here, so it does not escape the grapheme after it.
+        "\\" => Token::BackSlash(location),
+        "<" => Token::AngleBracketOpen(location),
+        ">" => Token::AngleBracketClose(location),
+        "{" => Token::CurlyBracketOpen(location),
+        "}" => Token::CurlyBracketClose(location),
+        "[" => Token::SquareBracketOpen(location),
+        "]" => Token::SquareBracketClose(location),
+        _ => return,
+    };
+    t.push_head(escaped);
+    t.pool.clear();
+}
 
-    tokens.push(Token::BackSlash(Location {
-        start: LocationData {
-            line,
-            character: column,
-        },
-        end: LocationData {
-            line,
-            character: column + 1,
-        },
-    }));
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
 
-    let t2 = Tokenizer {
-        column: Some(column + 1),
-        token_start_column: Some(column + 1),
-        untreated: Some(tail),
-        tokens: Some(tokens),
-        ..Default::default()
+    use crate::{
+        tokenize::tokenize_str,
+        tokens::{mock_location, Token::*},
     };
-    dispatch(&t.merge(&t2))
+
+    #[test]
+    fn test_escaped_brackets_in_brackets() -> Result<()> {
+        let input = "[std.* \\[a\\]]";
+        let expected_output = vec![
+            SquareBracketOpen(mock_location()),
+            Module("std".to_string(), mock_location()),
+            Dot(mock_location()),
+            Ident("*".to_string(), mock_location()),
+            WhiteSpace(mock_location()),
+            BackSlash(mock_location()),
+            SquareBracketOpen(mock_location()),
+            Text("a".to_string(), mock_location()),
+            BackSlash(mock_location()),
+            SquareBracketClose(mock_location()),
+            SquareBracketClose(mock_location()),
+            EOF(mock_location()),
+        ];
+        let mut actual_output = tokenize_str(input)?;
+        for token in actual_output.iter_mut() {
+            token.set_location(mock_location());
+        }
+        assert_eq!(expected_output, actual_output);
+        Ok(())
+    }
+
+    #[test]
+    fn test_escaped_backslash_before_command() -> Result<()> {
+        let input = "\\\\[std.* a]";
+        let expected_output = vec![
+            BackSlash(mock_location()),
+            BackSlash(mock_location()),
+            SquareBracketOpen(mock_location()),
+            Module("std".to_string(), mock_location()),
+            Dot(mock_location()),
+            Ident("*".to_string(), mock_location()),
+            WhiteSpace(mock_location()),
+            Text("a".to_string(), mock_location()),
+            SquareBracketClose(mock_location()),
+            EOF(mock_location()),
+        ];
+        let mut actual_output = tokenize_str(input)?;
+        for token in actual_output.iter_mut() {
+            token.set_location(mock_location());
+        }
+        assert_eq!(expected_output, actual_output);
+        Ok(())
+    }
 }
@@ -0,0 +1,16 @@
+use crate::{tokenizer::Tokenizer, tokens::Token};
+
+// A comment starts with `//` at the beginning of a line and lasts until the end of the line.
+pub fn tokenize(t: &mut Tokenizer) {
+    t.advance();
+    t.advance();
+    while !matches!(t.peek(0), "\n" | "\0") {
+        t.pool.push_str(t.peek(0));
+        t.advance();
+    }
+    let location = t.span_location();
+    t.tokens
+        .push(Token::Comment(std::mem::take(&mut t.pool), location));
+    t.token_start_column = t.column;
+    t.token_start_offset = t.offset;
+}
@@ -1,52 +1,9 @@
-use crate::{
-    dispatch::dispatch,
-    tokenizer::Tokenizer,
-    tokens::{Location, LocationData, Token},
-    utils::separate,
-};
-use anyhow::Result;
-
-pub fn tokenize(t: &Tokenizer) -> Result<Vec<Token>> {
-    let s = t
-        .untreated
-        .clone()
-        .ok_or_else(|| anyhow::anyhow!("`t.untreated` is not set"))?;
-    let (_, tail) = separate(&s);
-
-    let mut tokens = t
-        .tokens
-        .clone()
-        .ok_or_else(|| anyhow::anyhow!("`t.tokens` is not set"))?;
-    let line = t
-        .line
-        .ok_or_else(|| anyhow::anyhow!("`t.line` is not set"))?;
-    let column = t
-        .column
-        .ok_or_else(|| anyhow::anyhow!("`t.column` is not set"))?;
-    let curly_nest_count = t
-        .curly_nest_count
-        .ok_or_else(|| anyhow::anyhow!("`t.curly_nest_count` is not set"))?;
-
-    tokens.push(Token::CurlyBracketOpen(Location {
-        start: LocationData {
-            line,
-            character: column,
-        },
-        end: LocationData {
-            line,
-            character: column + 1,
-        },
-    }));
-
-    let t2 = Tokenizer {
-        column: Some(column + 1),
-        token_start_column: Some(column + 1),
-        untreated: Some(tail),
-        pool: Some(String::new()),
-        tokens: Some(tokens),
-        curly_nest_count: Some(curly_nest_count + 1),
-        looking_for_identifier: Some(true),
-        ..Default::default()
-    };
-    dispatch(&t.merge(&t2))
+use crate::{tokenizer::Tokenizer, tokens::Token};
+
+pub fn tokenize(t: &mut Tokenizer) {
+    t.push_head(Token::CurlyBracketOpen(t.head_location()));
+    t.pool.clear();
+    t.curly_nest_count += 1;
+    t.curly_nests.push(t.nest_count());
+    t.looking_for_identifier = true;
 }
@@ -0,0 +1,425 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::IsTerminal,
+};
+
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthChar;
+
+use crate::{
+    line_index::{LineIndex, PositionEncoding},
+    source_map::SourceMap,
+    tokens::Location,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Hint,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Info => write!(f, "info"),
+            Severity::Hint => write!(f, "hint"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Label {
+    pub location: Location,
+    pub message: Option<String>,
+}
+
+/// A replacement of the source that resolves a diagnostic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fix {
+    pub message: String,
+    pub location: Location,
+    pub replacement: String,
+}
+
+/// An error or a warning reported by any phase of the compiler or by plugins.
+/// Plugins can return a diagnostic serialized as JSON as their error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// A stable code such as `B0003`.
+    pub code: String,
+    pub severity: Severity,
+    pub message: String,
+    /// `None` when the location is not known, e.g. for plugin errors before
+    /// the caller attaches the location of the command.
+    pub primary: Option<Label>,
+    #[serde(default)]
+    pub secondary: Vec<Label>,
+    #[serde(default)]
+    pub help: Option<String>,
+    #[serde(default)]
+    pub fix: Option<Fix>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &str, message: impl Into<String>, location: Location) -> Self {
+        Self::new(code, Severity::Error, message, Some(location))
+    }
+
+    pub fn warning(code: &str, message: impl Into<String>, location: Location) -> Self {
+        Self::new(code, Severity::Warning, message, Some(location))
+    }
+
+    pub fn new(
+        code: &str,
+        severity: Severity,
+        message: impl Into<String>,
+        location: Option<Location>,
+    ) -> Self {
+        Self {
+            code: code.to_string(),
+            severity,
+            message: message.into(),
+            primary: location.map(|location| Label {
+                location,
+                message: None,
+            }),
+            secondary: vec![],
+            help: None,
+            fix: None,
+        }
+    }
+
+    /// Sets the message shown at the primary location.
+    pub fn with_primary_message(mut self, message: impl Into<String>) -> Self {
+        if let Some(primary) = self.primary.as_mut() {
+            primary.message = Some(message.into());
+        }
+        self
+    }
+
+    pub fn with_label(mut self, location: Location, message: impl Into<String>) -> Self {
+        self.secondary.push(Label {
+            location,
+            message: Some(message.into()),
+        });
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn with_fix(
+        mut self,
+        message: impl Into<String>,
+        location: Location,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.fix = Some(Fix {
+            message: message.into(),
+            location,
+            replacement: replacement.into(),
+        });
+        self
+    }
+
+    /// Converts an error into a diagnostic located at `location`.
+    /// A diagnostic in the error is kept, and only its missing location is filled.
+    pub fn from_error(error: &anyhow::Error, code: &str, location: Location) -> Self {
+        match error.downcast_ref::<Diagnostic>() {
+            Some(diagnostic) => diagnostic.clone().or_at(location),
+            None => Self::error(code, error.to_string(), location),
+        }
+    }
+
+    /// Uses `location` as the primary location if the diagnostic does not have one.
+    pub fn or_at(mut self, location: Location) -> Self {
+        if self.primary.is_none() {
+            self.primary = Some(Label {
+                location,
+                message: None,
+            });
+        }
+        self
+    }
+
+    pub fn location(&self) -> Option<Location> {
+        self.primary.as_ref().map(|label| label.location.clone())
+    }
+
+    /// Renders the diagnostic without colour.
+    pub fn render(&self, source_map: &SourceMap) -> String {
+        self.render_with_color(source_map, false)
+    }
+
+    /// Renders the diagnostic with the source lines of its labels underlined, like:
+    ///
+    /// ```text
+    /// error[B0014]: Unexpected comma
+    ///  --> docs/index.[]:1:10
+    ///   |
+    /// 1 | [std.* a,, b]
+    ///   |          ^
+    ///   = help: remove the comma
+    /// ```
+    pub fn render_with_color(&self, source_map: &SourceMap, color: bool) -> String {
+        let style = Style { color };
+        let mut labels = vec![];
+        if let Some(primary) = &self.primary {
+            labels.push((primary, true));
+        }
+        labels.extend(self.secondary.iter().map(|label| (label, false)));
+
+        let mut out = format!(
+            "{}{}",
+            style.paint(
+                self.severity.color(),
+                &format!("{}[{}]", self.severity, self.code)
+            ),
+            style.paint(BOLD, &format!(": {}", self.message)),
+        );
+        let gutter = labels
+            .iter()
+            .map(|(label, _)| label.location.start.line + 1)
+            .max()
+            .unwrap_or(0)
+            .to_string()
+            .len();
+        let pad = " ".repeat(gutter);
+        let bar = style.paint(BLUE, "|");
+
+        // Labels are grouped by file, keeping the order in which the files first appear.
+        let mut files = vec![];
+        for (label, _) in &labels {
+            if !files.contains(&label.location.file) {
+                files.push(label.location.file);
+            }
+        }
+        for (i, file) in files.iter().enumerate() {
+            let mut group = labels
+                .iter()
+                .filter(|(label, _)| label.location.file == *file)
+                .collect::<Vec<_>>();
+            let arrow = if i == 0 { "-->" } else { ":::" };
+            out.push_str(&format!(
+                "\n{}{} {}",
+                pad,
+                style.paint(BLUE, arrow),
+                source_map.render_location(&group[0].0.location)
+            ));
+            let source = match source_map.get(*file) {
+                Some(source) => source,
+                None => continue,
+            };
+            let index = LineIndex::new(&source.text);
+            out.push_str(&format!("\n{} {}", pad, bar));
+            group.sort_by_key(|(label, _)| label.location.start.offset);
+            let mut previous_line = None;
+            for (label, primary) in group {
+                let (line, start) = index.position(&label.location.start, PositionEncoding::Utf32);
+                let (end_line, end) = index.position(&label.location.end, PositionEncoding::Utf32);
+                let text = source.text.lines().nth(line).unwrap_or_default();
+                if previous_line != Some(line) {
+                    if previous_line.is_some_and(|previous| previous + 1 < line) {
+                        out.push_str(&format!("\n{}", style.paint(BLUE, "...")));
+                    }
+                    out.push_str(&format!(
+                        "\n{} {} {}",
+                        style.paint(BLUE, &format!("{:>gutter$}", line + 1)),
+                        bar,
+                        text.replace('\t', &" ".repeat(TAB_WIDTH))
+                    ));
+                    previous_line = Some(line);
+                }
+                // A label spanning several lines is underlined to the end of its first line.
+                let end = if end_line > line {
+                    text.chars().count()
+                } else {
+                    end
+                };
+                let offset = width(text.chars().take(start));
+                let length = width(text.chars().skip(start).take(end.saturating_sub(start))).max(1);
+                let (mark, mark_color) = if *primary {
+                    ("^", self.severity.color())
+                } else {
+                    ("-", BLUE)
+                };
+                let mut underline = mark.repeat(length);
+                if let Some(message) = &label.message {
+                    underline.push(' ');
+                    underline.push_str(message);
+                }
+                out.push_str(&format!(
+                    "\n{} {} {}{}",
+                    pad,
+                    bar,
+                    " ".repeat(offset),
+                    style.paint(mark_color, &underline)
+                ));
+            }
+        }
+        if let Some(help) = &self.help {
+            out.push_str(&format!(
+                "\n{} {} {}",
+                pad,
+                style.paint(BLUE, "="),
+                style.paint(BOLD, "help:")
+            ));
+            out.push_str(&format!(" {}", help));
+        }
+        if let Some(fix) = &self.fix {
+            out.push_str(&format!(
+                "\n{} {} {} {}",
+                pad,
+                style.paint(BLUE, "="),
+                style.paint(BOLD, "fix:"),
+                fix.message
+            ));
+        }
+        out
+    }
+}
+
+const BOLD: &str = "1";
+const BLUE: &str = "1;34";
+
+impl Severity {
+    fn color(&self) -> &'static str {
+        match self {
+            Severity::Error => "1;31",
+            Severity::Warning => "1;33",
+            Severity::Info | Severity::Hint => "1;36",
+        }
+    }
+}
+
+struct Style {
+    color: bool,
+}
+
+impl Style {
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Tabs in source lines are rendered as this many spaces.
+const TAB_WIDTH: usize = 4;
+
+/// The number of terminal columns taken by `chars`, counting East Asian wide characters as two.
+fn width(chars: impl Iterator<Item = char>) -> usize {
+    chars
+        .map(|c| match c {
+            '\t' => TAB_WIDTH,
+            c => c.width().unwrap_or(0),
+        })
+        .sum()
+}
+
+/// Whether diagnostics written to the standard error should be coloured.
+/// Colour is used only on a terminal and can be disabled with `NO_COLOR`.
+pub fn stderr_supports_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none() && std::io::stderr().is_terminal()
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Renders an error, using the source map if it is a diagnostic.
+pub fn render_error(error: &anyhow::Error, source_map: &SourceMap, color: bool) -> String {
+    match error.downcast_ref::<Diagnostic>() {
+        Some(diagnostic) => diagnostic.render_with_color(source_map, color),
+        None => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::{
+        source_map::SourceMap,
+        tokenize::tokenize_file,
+        tokens::{Location, LocationData, Token},
+    };
+
+    use super::Diagnostic;
+
+    #[test]
+    fn test_render_diagnostic() {
+        let mut source_map = SourceMap::new();
+        let file = source_map.add("docs/index.[]", "[std.* a,, b]\n".to_string());
+        let location = |character| Location {
+            file,
+            start: LocationData {
+                line: 0,
+                character,
+                offset: character,
+            },
+            end: LocationData {
+                line: 0,
+                character: character + 1,
+                offset: character + 1,
+            },
+        };
+        let diagnostic = Diagnostic::error("B0014", "Unexpected comma", location(9))
+            .with_label(location(8), "previous comma")
+            .with_help("remove the comma")
+            .with_fix("Remove the comma", location(9), "");
+        let expected = r#"error[B0014]: Unexpected comma
+ --> docs/index.[]:1:10
+  |
+1 | [std.* a,, b]
+  |         - previous comma
+  |          ^
+  = help: remove the comma
+  = fix: Remove the comma"#;
+        assert_eq!(diagnostic.render(&source_map), expected);
+        assert!(diagnostic
+            .render_with_color(&source_map, true)
+            .contains("\x1b[1;31merror[B0014]\x1b[0m"));
+    }
+
+    #[test]
+    fn test_render_diagnostic_with_wide_characters() -> Result<()> {
+        let mut source_map = SourceMap::new();
+        let file = source_map.add("docs/ja.[]", "こんにちは🥰\n\n\t[std.* 世界\n".to_string());
+        let tokens = tokenize_file(&source_map, file)?;
+        let location = tokens
+            .iter()
+            .find(|token| matches!(token, Token::SquareBracketOpen(_)))
+            .unwrap()
+            .get_location();
+        let bracket = Location {
+            end: tokens[tokens.len() - 2].get_location().end,
+            ..location
+        };
+        let diagnostic = Diagnostic::error("B0007", "Square bracket not closed", bracket.clone())
+            .with_label(tokens[0].get_location(), "text");
+        let expected = r#"error[B0007]: Square bracket not closed
+ --> docs/ja.[]:3:2
+  |
+1 | こんにちは🥰
+  | ------------ text
+...
+3 |     [std.* 世界
+  |     ^^^^^^^^^^^"#;
+        assert_eq!(diagnostic.render(&source_map), expected);
+        Ok(())
+    }
+}
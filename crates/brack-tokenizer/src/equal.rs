@@ -0,0 +1,40 @@
+use crate::{tokenizer::Tokenizer, tokens::Token};
+
+pub fn tokenize(t: &mut Tokenizer) {
+    t.push_head(Token::Equal(t.head_location()));
+    t.pool.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::{
+        tokenize::tokenize_str,
+        tokens::{mock_location, Token::*},
+    };
+
+    #[test]
+    fn test_equal_only_in_brackets() -> Result<()> {
+        let input = "a=b [std.x c=d]";
+        let expected_output = vec![
+            Text("a=b ".to_string(), mock_location()),
+            SquareBracketOpen(mock_location()),
+            Module("std".to_string(), mock_location()),
+            Dot(mock_location()),
+            Ident("x".to_string(), mock_location()),
+            WhiteSpace(mock_location()),
+            Text("c".to_string(), mock_location()),
+            Equal(mock_location()),
+            Text("d".to_string(), mock_location()),
+            SquareBracketClose(mock_location()),
+            EOF(mock_location()),
+        ];
+        let mut actual_output = tokenize_str(input)?;
+        for token in actual_output.iter_mut() {
+            token.set_location(mock_location());
+        }
+        assert_eq!(expected_output, actual_output);
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{
+    source_map::FileId,
+    tokenizer::Tokenizer,
+    tokens::{Location, LocationData, Token},
+};
+
+const FENCE: &str = "+++";
+
+/// A TOML block fenced by `+++` lines at the very beginning of a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrontMatter {
+    /// The TOML between the fences.
+    pub content: String,
+    /// The whole block including the fences and the newline after the closing fence.
+    pub source: String,
+    pub location: Location,
+}
+
+pub fn front_matter(text: &str, file: FileId) -> Option<FrontMatter> {
+    let mut lines = text.split_inclusive('\n');
+    let opening_fence = lines.next()?;
+    if !is_fence(opening_fence) {
+        return None;
+    }
+    let content_start = opening_fence.len();
+    let mut offset = content_start;
+    for (line, text_of_line) in lines.enumerate() {
+        if is_fence(text_of_line) {
+            let end = offset + text_of_line.len();
+            return Some(FrontMatter {
+                content: text[content_start..offset].to_string(),
+                source: text[..end].to_string(),
+                location: Location {
+                    file,
+                    start: LocationData {
+                        line: 0,
+                        character: 0,
+                        offset: 0,
+                    },
+                    end: LocationData {
+                        line: line + 1,
+                        character: FENCE.len(),
+                        offset: offset + FENCE.len(),
+                    },
+                },
+            });
+        }
+        offset += text_of_line.len();
+    }
+    None
+}
+
+/// Whether a line including its line break is exactly `+++`.
+fn is_fence(line: &str) -> bool {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line) == FENCE
+}
+
+/// Moves the cursor past the front matter, which is tokenized as a single token.
+/// The fences may end with `\r\n`, which is a single grapheme.
+pub(crate) fn skip(t: &mut Tokenizer, front_matter: &FrontMatter) {
+    let start = t.head_location().start;
+    while t.offset < front_matter.source.len() {
+        let grapheme = t.peek(0);
+        if grapheme == "\n" || grapheme == "\r\n" {
+            t.offset += grapheme.len();
+            t.cursor += 1;
+            t.line += 1;
+            t.column = 0;
+        } else {
+            t.advance();
+        }
+    }
+    t.tokens.push(Token::FrontMatter(
+        front_matter.source.clone(),
+        Location {
+            file: t.file,
+            start,
+            end: t.head_location().start,
+        },
+    ));
+    t.token_start_line = t.line;
+    t.token_start_column = t.column;
+    t.token_start_offset = t.offset;
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::{
+        source_map::FileId,
+        tokenize::tokenize_str,
+        tokens::{Location, LocationData, Token},
+    };
+
+    use super::front_matter;
+
+    #[test]
+    fn test_front_matter() {
+        let text = "+++\ntitle = \"Brack\"\n+++\nHello";
+        let front_matter = front_matter(text, FileId::default()).unwrap();
+        assert_eq!(front_matter.content, "title = \"Brack\"\n");
+        assert_eq!(front_matter.source, "+++\ntitle = \"Brack\"\n+++\n");
+        assert_eq!(front_matter.location.end.line, 2);
+        assert!(super::front_matter("Hello\n+++\n+++\n", FileId::default()).is_none());
+        assert!(super::front_matter("+++\ntitle = \"Brack\"\n", FileId::default()).is_none());
+        assert!(super::front_matter("+++ \ntitle = 1\n+++\n", FileId::default()).is_none());
+        assert!(super::front_matter("+++\ntitle = 1\n+++\t\n", FileId::default()).is_none());
+    }
+
+    #[test]
+    fn test_front_matter_is_one_token() -> Result<()> {
+        let tokens = tokenize_str("+++\ndraft = true\n+++\nHello")?;
+        assert_eq!(
+            tokens[0],
+            Token::FrontMatter(
+                "+++\ndraft = true\n+++\n".to_string(),
+                Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 0,
+                        character: 0,
+                        offset: 0,
+                    },
+                    end: LocationData {
+                        line: 3,
+                        character: 0,
+                        offset: 21,
+                    },
+                },
+            )
+        );
+        assert_eq!(
+            tokens[1],
+            Token::Text(
+                "Hello".to_string(),
+                Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 3,
+                        character: 0,
+                        offset: 21,
+                    },
+                    end: LocationData {
+                        line: 3,
+                        character: 5,
+                        offset: 26,
+                    },
+                },
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_front_matter_with_crlf() -> Result<()> {
+        let text = "+++\r\ndraft = true\r\n+++\r\nHello";
+        let front_matter = front_matter(text, FileId::default()).unwrap();
+        assert_eq!(front_matter.content, "draft = true\r\n");
+        assert_eq!(front_matter.location.end.line, 2);
+        let tokens = tokenize_str(text)?;
+        assert_eq!(
+            tokens[1],
+            Token::Text(
+                "Hello".to_string(),
+                Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 3,
+                        character: 0,
+                        offset: 24,
+                    },
+                    end: LocationData {
+                        line: 3,
+                        character: 5,
+                        offset: 29,
+                    },
+                },
+            )
+        );
+        Ok(())
+    }
+}
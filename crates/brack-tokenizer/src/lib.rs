@@ -2,18 +2,24 @@ mod angle_bracket_close;
 mod angle_bracket_open;
 mod backslash;
 mod comma;
+mod comment;
 mod curly_bracket_close;
 mod curly_bracket_open;
+pub mod diagnostic;
 mod dispatch;
 mod dot;
+mod equal;
+pub mod front_matter;
 mod identifier;
+pub mod line_index;
 mod module;
 mod newline;
+mod raw;
+pub mod source_map;
 mod square_bracket_close;
 mod square_bracket_open;
 mod text;
 pub mod tokenize;
 mod tokenizer;
 pub mod tokens;
-mod utils;
 mod whitespace;
@@ -0,0 +1,87 @@
+use crate::tokens::LocationData;
+
+/// Unit in which the column of a position is counted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+    Utf32,
+}
+
+/// Converts byte offsets of a source text into lines and columns
+/// counted in a given encoding.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        for (i, c) in text.char_indices() {
+            if c == '\n' {
+                line_starts.push(i + 1);
+            }
+        }
+        Self { text, line_starts }
+    }
+
+    /// Returns the zero-based line and column of `location`.
+    pub fn position(&self, location: &LocationData, encoding: PositionEncoding) -> (usize, usize) {
+        let offset = location.offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_text = &self.text[self.line_starts[line]..offset];
+        let column = match encoding {
+            PositionEncoding::Utf8 => line_text.len(),
+            PositionEncoding::Utf16 => line_text.encode_utf16().count(),
+            PositionEncoding::Utf32 => line_text.chars().count(),
+        };
+        (line, column)
+    }
+
+    pub fn utf8_column(&self, location: &LocationData) -> usize {
+        self.position(location, PositionEncoding::Utf8).1
+    }
+
+    pub fn utf16_column(&self, location: &LocationData) -> usize {
+        self.position(location, PositionEncoding::Utf16).1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{LineIndex, PositionEncoding};
+    use crate::tokenize::tokenize_str;
+
+    #[test]
+    fn test_position_of_japanese_and_emoji() -> Result<()> {
+        let text = "こんにちは\n[std.* 🇯🇵]";
+        let tokens = tokenize_str(text)?;
+        let index = LineIndex::new(text);
+
+        // Text("🇯🇵") on the second line
+        let location = tokens[7].get_location();
+        assert_eq!(location.start.character, 7);
+        assert_eq!(index.utf8_column(&location.start), 7);
+        assert_eq!(index.utf16_column(&location.start), 7);
+        assert_eq!(index.utf8_column(&location.end), 15);
+        assert_eq!(index.utf16_column(&location.end), 11);
+        assert_eq!(
+            index.position(&location.end, PositionEncoding::Utf32),
+            (1, 9)
+        );
+
+        // EOF after the closing bracket
+        let location = tokens[9].get_location();
+        assert_eq!(location.start.character, 9);
+        assert_eq!(
+            index.position(&location.start, PositionEncoding::Utf16),
+            (1, 12)
+        );
+        Ok(())
+    }
+}
@@ -1,48 +1,110 @@
-use crate::{
-    dispatch::dispatch,
-    tokenizer::Tokenizer,
-    tokens::{Location, LocationData, Token},
-    utils::separate,
-};
-use anyhow::Result;
+use crate::{tokenizer::Tokenizer, tokens::Token};
 
-pub fn tokenize(t: &Tokenizer) -> Result<Vec<Token>> {
-    let s = t
-        .untreated
-        .clone()
-        .ok_or_else(|| anyhow::anyhow!("`t.untreated` is not set"))?;
-    let line = t
-        .line
-        .ok_or_else(|| anyhow::anyhow!("`t.line` is not set"))?;
-    let column = t
-        .column
-        .ok_or_else(|| anyhow::anyhow!("`t.column` is not set"))?;
-    let (_, tail) = separate(&s);
-    let mut tokens = t
-        .tokens
-        .clone()
-        .ok_or_else(|| anyhow::anyhow!("`t.tokens` is not set"))?;
+pub fn tokenize(t: &mut Tokenizer) {
+    // A newline directly in the arguments of a block command starts its body,
+    // unless it follows a trailing comma and the arguments continue on the next line.
+    let trailing_comma = matches!(
+        t.tokens
+            .iter()
+            .rev()
+            .find(|token| !matches!(token, Token::WhiteSpace(_))),
+        Some(Token::Comma(_))
+    );
+    t.tokens.push(Token::NewLine(t.head_location()));
+    let nest_count = t.nest_count();
+    if t.in_arguments() && t.curly_nests.last() == Some(&nest_count) && !trailing_comma {
+        t.body_nests.push(nest_count);
+    }
+    t.offset += t.peek(0).len();
+    t.cursor += 1;
+    t.line += 1;
+    t.column = 0;
+    t.token_start_line = t.line;
+    t.token_start_column = 0;
+    t.token_start_offset = t.offset;
+    t.pool.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::{tokenize::tokenize_str, tokens::Token};
+
+    fn describe(tokens: &[Token]) -> Vec<String> {
+        tokens
+            .iter()
+            .map(|token| match token {
+                Token::Text(text, _) => format!("Text({})", text),
+                token => format!("{:?}", token)
+                    .split('(')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_newline_starts_body_of_block_command() -> Result<()> {
+        let tokens = tokenize_str("{a.b x\nHello, world.\n\n{c.d\nOK.\n}\n}")?;
+        assert_eq!(
+            describe(&tokens),
+            vec![
+                "CurlyBracketOpen",
+                "Module",
+                "Dot",
+                "Ident",
+                "WhiteSpace",
+                "Text(x)",
+                "NewLine",
+                "Text(Hello, world.)",
+                "NewLine",
+                "NewLine",
+                "CurlyBracketOpen",
+                "Module",
+                "Dot",
+                "Ident",
+                "NewLine",
+                "Text(OK.)",
+                "NewLine",
+                "CurlyBracketClose",
+                "NewLine",
+                "CurlyBracketClose",
+                "EOF",
+            ]
+        );
+        Ok(())
+    }
 
-    tokens.push(Token::NewLine(Location {
-        start: LocationData {
-            line,
-            character: column,
-        },
-        end: LocationData {
-            line,
-            character: column + 1,
-        },
-    }));
+    #[test]
+    fn test_newline_after_trailing_comma_does_not_start_body() -> Result<()> {
+        let tokens = tokenize_str("{a.b x,\ny\nBody}")?;
+        assert_eq!(
+            describe(&tokens),
+            vec![
+                "CurlyBracketOpen",
+                "Module",
+                "Dot",
+                "Ident",
+                "WhiteSpace",
+                "Text(x)",
+                "Comma",
+                "NewLine",
+                "Text(y)",
+                "NewLine",
+                "Text(Body)",
+                "CurlyBracketClose",
+                "EOF",
+            ]
+        );
+        Ok(())
+    }
 
-    let t2 = Tokenizer {
-        line: Some(line + 1),
-        column: Some(0),
-        token_start_line: Some(line + 1),
-        token_start_column: Some(0),
-        untreated: Some(tail),
-        pool: Some(String::new()),
-        tokens: Some(tokens),
-        ..Default::default()
-    };
-    dispatch(&t.merge(&t2))
+    #[test]
+    fn test_newline_in_inline_command_does_not_start_body() -> Result<()> {
+        let tokens = tokenize_str("{a.b [c.d x,\ny]}")?;
+        assert!(describe(&tokens).contains(&"Comma".to_string()));
+        Ok(())
+    }
 }
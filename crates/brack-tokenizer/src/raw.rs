@@ -0,0 +1,112 @@
+use crate::{
+    tokenizer::Tokenizer,
+    tokens::{RawBlock, Token},
+};
+
+const MIN_FENCE_LENGTH: usize = 3;
+
+fn fence_length_at(t: &Tokenizer, n: usize) -> usize {
+    let mut length = 0;
+    while t.peek(n + length) == "`" {
+        length += 1;
+    }
+    length
+}
+
+// Returns the position of the first grapheme of the line following the one at `n`.
+fn next_line_at(t: &Tokenizer, n: usize) -> Option<usize> {
+    let mut n = n;
+    loop {
+        match t.peek(n) {
+            "\0" => return None,
+            "\n" => return Some(n + 1),
+            _ => n += 1,
+        }
+    }
+}
+
+// Returns the position of the closing fence, which consists of exactly `fence_length` backticks.
+fn closing_fence_at(t: &Tokenizer, fence_length: usize) -> Option<usize> {
+    let mut n = next_line_at(t, 0)?;
+    loop {
+        if fence_length_at(t, n) == fence_length && matches!(t.peek(n + fence_length), "\n" | "\0")
+        {
+            return Some(n);
+        }
+        n = next_line_at(t, n)?;
+    }
+}
+
+/// A raw block starts with a line of three or more backticks followed by an optional info string,
+/// and ends with a line of the same number of backticks.
+/// Without the closing fence, the opening line is tokenized as usual.
+pub fn is_raw_block(t: &Tokenizer) -> bool {
+    let fence_length = fence_length_at(t, 0);
+    fence_length >= MIN_FENCE_LENGTH && closing_fence_at(t, fence_length).is_some()
+}
+
+fn consume<'a>(t: &mut Tokenizer<'a>) -> &'a str {
+    let head = t.peek(0);
+    if head == "\n" {
+        t.offset += head.len();
+        t.cursor += 1;
+        t.line += 1;
+        t.column = 0;
+    } else {
+        t.advance();
+    }
+    head
+}
+
+pub fn tokenize(t: &mut Tokenizer) {
+    let fence_length = fence_length_at(t, 0);
+    let closing_fence = closing_fence_at(t, fence_length).unwrap_or_default();
+    let start = t.cursor;
+
+    let mut info = String::new();
+    for _ in 0..fence_length {
+        consume(t);
+    }
+    while t.peek(0) != "\n" {
+        info.push_str(consume(t));
+    }
+    consume(t);
+
+    let mut content = String::new();
+    while t.cursor < start + closing_fence {
+        content.push_str(consume(t));
+    }
+    for _ in 0..fence_length {
+        consume(t);
+    }
+
+    let location = t.span_location();
+    let info = if info.is_empty() { None } else { Some(info) };
+    t.tokens.push(Token::Raw(
+        RawBlock {
+            fence_length,
+            info,
+            content,
+        },
+        location,
+    ));
+    t.token_start_line = t.line;
+    t.token_start_column = t.column;
+    t.token_start_offset = t.offset;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn test_is_raw_block() {
+        assert!(super::is_raw_block(&Tokenizer::new("```\na\n```")));
+        assert!(super::is_raw_block(&Tokenizer::new(
+            "````rust\n```\n````\n"
+        )));
+        assert!(!super::is_raw_block(&Tokenizer::new("```\na\n````")));
+        assert!(!super::is_raw_block(&Tokenizer::new("``\na\n``")));
+        assert!(!super::is_raw_block(&Tokenizer::new("```a```")));
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    line_index::{LineIndex, PositionEncoding},
+    tokens::Location,
+};
+
+/// Identifier of a source file registered in a [`SourceMap`].
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct FileId(pub usize);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub text: String,
+}
+
+/// Registry of the source files compiled together.
+#[derive(Debug, Default, Clone)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<P: AsRef<Path>>(&mut self, path: P, text: String) -> FileId {
+        self.files.push(SourceFile {
+            path: path.as_ref().to_path_buf(),
+            text,
+        });
+        FileId(self.files.len() - 1)
+    }
+
+    /// Reads the file at `path` and registers it.
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<FileId> {
+        let text = std::fs::read_to_string(&path)?;
+        Ok(self.add(path, text))
+    }
+
+    pub fn get(&self, file: FileId) -> Option<&SourceFile> {
+        self.files.get(file.0)
+    }
+
+    pub fn find<P: AsRef<Path>>(&self, path: P) -> Option<FileId> {
+        self.files
+            .iter()
+            .position(|file| file.path == path.as_ref())
+            .map(FileId)
+    }
+
+    /// Renders the start of `location` as `path:line:column`,
+    /// where lines and columns are counted from 1.
+    pub fn render_location(&self, location: &Location) -> String {
+        match self.get(location.file) {
+            Some(file) => {
+                let index = LineIndex::new(&file.text);
+                let (line, column) = index.position(&location.start, PositionEncoding::Utf32);
+                format!("{}:{}:{}", file.path.display(), line + 1, column + 1)
+            }
+            None => format!(
+                "<unknown>:{}:{}",
+                location.start.line + 1,
+                location.start.character + 1
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{FileId, SourceMap};
+    use crate::tokenize::tokenize_file;
+
+    #[test]
+    fn test_render_location_of_each_file() -> Result<()> {
+        let mut source_map = SourceMap::new();
+        let main = source_map.add("docs/main.[]", "Hello\n[std.* World]".to_string());
+        let sub = source_map.add("docs/sub.[]", "こんにちは [std.* 世界]".to_string());
+        assert_eq!(main, FileId(0));
+        assert_eq!(sub, FileId(1));
+        assert_eq!(source_map.find("docs/sub.[]"), Some(sub));
+
+        let tokens = tokenize_file(&source_map, main)?;
+        let location = tokens[2].get_location();
+        assert_eq!(location.file, main);
+        assert_eq!(source_map.render_location(&location), "docs/main.[]:2:1");
+
+        let tokens = tokenize_file(&source_map, sub)?;
+        let location = tokens[1].get_location();
+        assert_eq!(location.file, sub);
+        assert_eq!(source_map.render_location(&location), "docs/sub.[]:1:7");
+        Ok(())
+    }
+}
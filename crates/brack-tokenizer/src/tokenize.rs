@@ -1,7 +1,13 @@
 use anyhow::Result;
 use std::{fs::File, io::Read, path::Path};
 
-use crate::{dispatch::dispatch, tokenizer::Tokenizer, tokens::Token};
+use crate::{
+    dispatch::dispatch,
+    front_matter::{self, front_matter},
+    source_map::{FileId, SourceMap},
+    tokenizer::Tokenizer,
+    tokens::Token,
+};
 
 pub fn tokenize<P: AsRef<Path>>(path: P) -> Result<Vec<Token>> {
     let mut file = File::open(&path)?;
@@ -10,28 +16,36 @@ pub fn tokenize<P: AsRef<Path>>(path: P) -> Result<Vec<Token>> {
     tokenize_str(&text)
 }
 
+/// Tokenizes a text whose locations point to the default file id.
 pub fn tokenize_str(text: &str) -> Result<Vec<Token>> {
-    let t = Tokenizer {
-        tokens: Some(vec![]),
-        line: Some(0),
-        column: Some(0),
-        pool: Some(String::new()),
-        token_start_line: Some(0),
-        token_start_column: Some(0),
-        untreated: Some(text.to_string()),
-        curly_nest_count: Some(0),
-        square_nest_count: Some(0),
-        angle_nest_count: Some(0),
-        looking_for_identifier: Some(false),
-        ..Default::default()
-    };
-    dispatch(&t)
+    tokenize_source(text, FileId::default())
+}
+
+/// Tokenizes a file registered in `source_map`.
+pub fn tokenize_file(source_map: &SourceMap, file: FileId) -> Result<Vec<Token>> {
+    let source = source_map
+        .get(file)
+        .ok_or_else(|| anyhow::anyhow!("File {:?} is not registered", file))?;
+    tokenize_source(&source.text, file)
+}
+
+fn tokenize_source(text: &str, file: FileId) -> Result<Vec<Token>> {
+    let mut t = Tokenizer::new(text);
+    t.file = file;
+    if let Some(front_matter) = front_matter(text, file) {
+        front_matter::skip(&mut t, &front_matter);
+    }
+    dispatch(&mut t);
+    Ok(t.tokens)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::tokenize;
-    use crate::tokens::{Location, LocationData, Token};
+    use super::{tokenize, tokenize_str};
+    use crate::{
+        source_map::FileId,
+        tokens::{Location, LocationData, RawBlock, Token},
+    };
     use anyhow::Result;
     use pretty_assertions::assert_eq;
 
@@ -49,24 +63,30 @@ mod tests {
                 Token::Text(
                     "Hello, World!".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 0,
+                            offset: 0,
                         },
                         end: LocationData {
                             line: 0,
                             character: 13,
+                            offset: 13,
                         }
                     },
                 ),
                 Token::EOF(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 13,
+                        offset: 13,
                     },
                     end: LocationData {
                         line: 0,
                         character: 13,
+                        offset: 13,
                     }
                 }),
             ]
@@ -88,103 +108,130 @@ mod tests {
                 Token::Text(
                     "Hello, ".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 0,
+                            offset: 0,
                         },
                         end: LocationData {
                             line: 0,
                             character: 7,
+                            offset: 7,
                         },
                     },
                 ),
                 Token::SquareBracketOpen(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 7,
+                        offset: 7,
                     },
                     end: LocationData {
                         line: 0,
                         character: 8,
+                        offset: 8,
                     },
                 }),
                 Token::Module(
                     "std".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 8,
+                            offset: 8,
                         },
                         end: LocationData {
                             line: 0,
                             character: 11,
+                            offset: 11,
                         },
                     }
                 ),
                 Token::Dot(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 11,
+                        offset: 11,
                     },
                     end: LocationData {
                         line: 0,
                         character: 12,
+                        offset: 12,
                     },
                 }),
                 Token::Ident(
                     "*".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 12,
+                            offset: 12,
                         },
                         end: LocationData {
                             line: 0,
                             character: 13,
+                            offset: 13,
                         },
                     }
                 ),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 13,
+                        offset: 13,
                     },
                     end: LocationData {
                         line: 0,
                         character: 14,
+                        offset: 14,
                     },
                 }),
                 Token::Text(
                     "World!".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 14,
+                            offset: 14,
                         },
                         end: LocationData {
                             line: 0,
                             character: 20,
+                            offset: 20,
                         },
                     }
                 ),
                 Token::SquareBracketClose(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 20,
+                        offset: 20,
                     },
                     end: LocationData {
                         line: 0,
                         character: 21,
+                        offset: 21,
                     },
                 }),
                 Token::EOF(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 21,
+                        offset: 21,
                     },
                     end: LocationData {
                         line: 0,
                         character: 21,
+                        offset: 21,
                     },
                 }),
             ]
@@ -206,103 +253,130 @@ mod tests {
                 Token::Text(
                     "Hello, ".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 0,
+                            offset: 0,
                         },
                         end: LocationData {
                             line: 0,
                             character: 7,
+                            offset: 7,
                         },
                     }
                 ),
                 Token::CurlyBracketOpen(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 7,
+                        offset: 7,
                     },
                     end: LocationData {
                         line: 0,
                         character: 8,
+                        offset: 8,
                     },
                 }),
                 Token::Module(
                     "std".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 8,
+                            offset: 8,
                         },
                         end: LocationData {
                             line: 0,
                             character: 11,
+                            offset: 11,
                         },
                     }
                 ),
                 Token::Dot(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 11,
+                        offset: 11,
                     },
                     end: LocationData {
                         line: 0,
                         character: 12,
+                        offset: 12,
                     },
                 }),
                 Token::Ident(
                     "*".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 12,
+                            offset: 12,
                         },
                         end: LocationData {
                             line: 0,
                             character: 13,
+                            offset: 13,
                         },
                     }
                 ),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 13,
+                        offset: 13,
                     },
                     end: LocationData {
                         line: 0,
                         character: 14,
+                        offset: 14,
                     },
                 }),
                 Token::Text(
                     "World!".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 14,
+                            offset: 14,
                         },
                         end: LocationData {
                             line: 0,
                             character: 20,
+                            offset: 20,
                         },
                     }
                 ),
                 Token::CurlyBracketClose(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 20,
+                        offset: 20,
                     },
                     end: LocationData {
                         line: 0,
                         character: 21,
+                        offset: 21,
                     },
                 }),
                 Token::EOF(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 21,
+                        offset: 21,
                     },
                     end: LocationData {
                         line: 0,
                         character: 21,
+                        offset: 21,
                     },
                 }),
             ]
@@ -324,80 +398,101 @@ mod tests {
                 Token::Text(
                     "Hello, ".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 0,
+                            offset: 0,
                         },
                         end: LocationData {
                             line: 0,
                             character: 7,
+                            offset: 7,
                         },
                     }
                 ),
                 Token::AngleBracketOpen(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 7,
+                        offset: 7,
                     },
                     end: LocationData {
                         line: 0,
                         character: 8,
+                        offset: 8,
                     },
                 }),
                 Token::Ident(
                     "*".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 8,
+                            offset: 8,
                         },
                         end: LocationData {
                             line: 0,
                             character: 9,
+                            offset: 9,
                         },
                     }
                 ),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 9,
+                        offset: 9,
                     },
                     end: LocationData {
                         line: 0,
                         character: 10,
+                        offset: 10,
                     },
                 }),
                 Token::Text(
                     "World!".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 10,
+                            offset: 10,
                         },
                         end: LocationData {
                             line: 0,
                             character: 16,
+                            offset: 16,
                         },
                     }
                 ),
                 Token::AngleBracketClose(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 16,
+                        offset: 16,
                     },
                     end: LocationData {
                         line: 0,
                         character: 17,
+                        offset: 17,
                     },
                 }),
                 Token::EOF(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 17,
+                        offset: 17,
                     },
                     end: LocationData {
                         line: 0,
                         character: 17,
+                        offset: 17,
                     },
                 }),
             ]
@@ -419,169 +514,214 @@ mod tests {
                 Token::Text(
                     "Hello, ".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 0,
+                            offset: 0,
                         },
                         end: LocationData {
                             line: 0,
                             character: 7,
+                            offset: 7,
                         },
                     }
                 ),
                 Token::SquareBracketOpen(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 7,
+                        offset: 7,
                     },
                     end: LocationData {
                         line: 0,
                         character: 8,
+                        offset: 8,
                     },
                 }),
                 Token::Module(
                     "std".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 8,
+                            offset: 8,
                         },
                         end: LocationData {
                             line: 0,
                             character: 11,
+                            offset: 11,
                         },
                     }
                 ),
                 Token::Dot(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 11,
+                        offset: 11,
                     },
                     end: LocationData {
                         line: 0,
                         character: 12,
+                        offset: 12,
                     },
                 }),
                 Token::Ident(
                     "@".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 12,
+                            offset: 12,
                         },
                         end: LocationData {
                             line: 0,
                             character: 13,
+                            offset: 13,
                         },
                     }
                 ),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 13,
+                        offset: 13,
                     },
                     end: LocationData {
                         line: 0,
                         character: 14,
+                        offset: 14,
                     },
                 }),
                 Token::Text(
                     "World!".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 14,
+                            offset: 14,
                         },
                         end: LocationData {
                             line: 0,
                             character: 20,
+                            offset: 20,
                         },
                     }
                 ),
                 Token::Comma(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 20,
+                        offset: 20,
                     },
                     end: LocationData {
                         line: 0,
                         character: 21,
+                        offset: 21,
                     },
                 }),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 21,
+                        offset: 21,
                     },
                     end: LocationData {
                         line: 0,
                         character: 22,
+                        offset: 22,
                     },
                 }),
                 Token::Text(
                     "https://example".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 22,
+                            offset: 22,
                         },
                         end: LocationData {
                             line: 0,
                             character: 37,
+                            offset: 37,
                         },
                     }
                 ),
                 Token::BackSlash(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 37,
+                        offset: 37,
                     },
                     end: LocationData {
                         line: 0,
                         character: 38,
+                        offset: 38,
                     },
                 }),
                 Token::Dot(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 38,
+                        offset: 38,
                     },
                     end: LocationData {
                         line: 0,
                         character: 39,
+                        offset: 39,
                     },
                 }),
                 Token::Text(
                     "com/".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 39,
+                            offset: 39,
                         },
                         end: LocationData {
                             line: 0,
                             character: 43,
+                            offset: 43,
                         },
                     }
                 ),
                 Token::SquareBracketClose(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 43,
+                        offset: 43,
                     },
                     end: LocationData {
                         line: 0,
                         character: 44,
+                        offset: 44,
                     },
                 }),
                 Token::EOF(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 44,
+                        offset: 44,
                     },
                     end: LocationData {
                         line: 0,
                         character: 44,
+                        offset: 44,
                     },
                 }),
             ]
@@ -603,235 +743,298 @@ mod tests {
                 Token::Text(
                     "Hello, ".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 0,
+                            offset: 0,
                         },
                         end: LocationData {
                             line: 0,
                             character: 7,
+                            offset: 7,
                         },
                     }
                 ),
                 Token::SquareBracketOpen(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 7,
+                        offset: 7,
                     },
                     end: LocationData {
                         line: 0,
                         character: 8,
+                        offset: 8,
                     },
                 }),
                 Token::Module(
                     "std".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 8,
+                            offset: 8,
                         },
                         end: LocationData {
                             line: 0,
                             character: 11,
+                            offset: 11,
                         },
                     }
                 ),
                 Token::Dot(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 11,
+                        offset: 11,
                     },
                     end: LocationData {
                         line: 0,
                         character: 12,
+                        offset: 12,
                     },
                 }),
                 Token::Ident(
                     "*".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 12,
+                            offset: 12,
                         },
                         end: LocationData {
                             line: 0,
                             character: 13,
+                            offset: 13,
                         },
                     }
                 ),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 13,
+                        offset: 13,
                     },
                     end: LocationData {
                         line: 0,
                         character: 14,
+                        offset: 14,
                     },
                 }),
                 Token::SquareBracketOpen(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 14,
+                        offset: 14,
                     },
                     end: LocationData {
                         line: 0,
                         character: 15,
+                        offset: 15,
                     },
                 }),
                 Token::Module(
                     "std".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 15,
+                            offset: 15,
                         },
                         end: LocationData {
                             line: 0,
                             character: 18,
+                            offset: 18,
                         },
                     }
                 ),
                 Token::Dot(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 18,
+                        offset: 18,
                     },
                     end: LocationData {
                         line: 0,
                         character: 19,
+                        offset: 19,
                     },
                 }),
                 Token::Ident(
                     "@".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 19,
+                            offset: 19,
                         },
                         end: LocationData {
                             line: 0,
                             character: 20,
+                            offset: 20,
                         },
                     }
                 ),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 20,
+                        offset: 20,
                     },
                     end: LocationData {
                         line: 0,
                         character: 21,
+                        offset: 21,
                     },
                 }),
                 Token::Text(
                     "World!".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 21,
+                            offset: 21,
                         },
                         end: LocationData {
                             line: 0,
                             character: 27,
+                            offset: 27,
                         },
                     }
                 ),
                 Token::Comma(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 27,
+                        offset: 27,
                     },
                     end: LocationData {
                         line: 0,
                         character: 28,
+                        offset: 28,
                     },
                 }),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 28,
+                        offset: 28,
                     },
                     end: LocationData {
                         line: 0,
                         character: 29,
+                        offset: 29,
                     },
                 }),
                 Token::Text(
                     "https://example".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 29,
+                            offset: 29,
                         },
                         end: LocationData {
                             line: 0,
                             character: 44,
+                            offset: 44,
                         },
                     }
                 ),
                 Token::BackSlash(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 44,
+                        offset: 44,
                     },
                     end: LocationData {
                         line: 0,
                         character: 45,
+                        offset: 45,
                     },
                 }),
                 Token::Dot(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 45,
+                        offset: 45,
                     },
                     end: LocationData {
                         line: 0,
                         character: 46,
+                        offset: 46,
                     },
                 }),
                 Token::Text(
                     "com/".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 46,
+                            offset: 46,
                         },
                         end: LocationData {
                             line: 0,
                             character: 50,
+                            offset: 50,
                         },
                     }
                 ),
                 Token::SquareBracketClose(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 50,
+                        offset: 50,
                     },
                     end: LocationData {
                         line: 0,
                         character: 51,
+                        offset: 51,
                     },
                 }),
                 Token::SquareBracketClose(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 51,
+                        offset: 51,
                     },
                     end: LocationData {
                         line: 0,
                         character: 52,
+                        offset: 52,
                     },
                 }),
                 Token::EOF(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 52,
+                        offset: 52,
                     },
                     end: LocationData {
                         line: 0,
                         character: 52,
+                        offset: 52,
                     },
                 }),
             ]
@@ -854,347 +1057,440 @@ mod tests {
                 Token::Text(
                     "Hello,".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 0,
+                            offset: 0,
                         },
                         end: LocationData {
                             line: 0,
                             character: 6,
+                            offset: 6,
                         },
                     }
                 ),
                 Token::NewLine(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 6,
+                        offset: 6,
                     },
                     end: LocationData {
                         line: 0,
                         character: 7,
+                        offset: 7,
                     },
                 }),
                 Token::Text(
                     "World,".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 1,
                             character: 0,
+                            offset: 7,
                         },
                         end: LocationData {
                             line: 1,
                             character: 6,
+                            offset: 13,
                         },
                     }
                 ),
                 Token::NewLine(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 1,
                         character: 6,
+                        offset: 13,
                     },
                     end: LocationData {
                         line: 1,
                         character: 7,
+                        offset: 14,
                     },
                 }),
                 Token::CurlyBracketOpen(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 2,
                         character: 0,
+                        offset: 14,
                     },
                     end: LocationData {
                         line: 2,
                         character: 1,
+                        offset: 15,
                     },
                 }),
                 Token::Module(
                     "std".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 2,
                             character: 1,
+                            offset: 15,
                         },
                         end: LocationData {
                             line: 2,
                             character: 4,
+                            offset: 18,
                         },
                     }
                 ),
                 Token::Dot(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 2,
                         character: 4,
+                        offset: 18,
                     },
                     end: LocationData {
                         line: 2,
                         character: 5,
+                        offset: 19,
                     },
                 }),
                 Token::Ident(
                     "**".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 2,
                             character: 5,
+                            offset: 19,
                         },
                         end: LocationData {
                             line: 2,
                             character: 7,
+                            offset: 21,
                         },
                     }
                 ),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 2,
                         character: 7,
+                        offset: 21,
                     },
                     end: LocationData {
                         line: 2,
                         character: 8,
+                        offset: 22,
                     },
                 }),
                 Token::Text(
                     "Contact".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 2,
                             character: 8,
+                            offset: 22,
                         },
                         end: LocationData {
                             line: 2,
                             character: 15,
+                            offset: 29,
                         },
                     }
                 ),
                 Token::CurlyBracketClose(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 2,
                         character: 15,
+                        offset: 29,
                     },
                     end: LocationData {
                         line: 2,
                         character: 16,
+                        offset: 30,
                     },
                 }),
                 Token::NewLine(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 2,
                         character: 16,
+                        offset: 30,
                     },
                     end: LocationData {
                         line: 2,
                         character: 17,
+                        offset: 31,
                     },
                 }),
                 Token::SquareBracketOpen(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 3,
                         character: 0,
+                        offset: 31,
                     },
                     end: LocationData {
                         line: 3,
                         character: 1,
+                        offset: 32,
                     },
                 }),
                 Token::Module(
                     "std".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 3,
                             character: 1,
+                            offset: 32,
                         },
                         end: LocationData {
                             line: 3,
                             character: 4,
+                            offset: 35,
                         },
                     }
                 ),
                 Token::Dot(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 3,
                         character: 4,
+                        offset: 35,
                     },
                     end: LocationData {
                         line: 3,
                         character: 5,
+                        offset: 36,
                     },
                 }),
                 Token::Ident(
                     "@".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 3,
                             character: 5,
+                            offset: 36,
                         },
                         end: LocationData {
                             line: 3,
                             character: 6,
+                            offset: 37,
                         },
                     }
                 ),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 3,
                         character: 6,
+                        offset: 37,
                     },
                     end: LocationData {
                         line: 3,
                         character: 7,
+                        offset: 38,
                     },
                 }),
                 Token::Text(
                     "My".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 3,
                             character: 7,
+                            offset: 38,
                         },
                         end: LocationData {
                             line: 3,
                             character: 9,
+                            offset: 40,
                         },
                     }
                 ),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 3,
                         character: 9,
+                        offset: 40,
                     },
                     end: LocationData {
                         line: 3,
                         character: 10,
+                        offset: 41,
                     },
                 }),
                 Token::Text(
                     "website".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 3,
                             character: 10,
+                            offset: 41,
                         },
                         end: LocationData {
                             line: 3,
                             character: 17,
+                            offset: 48,
                         },
                     }
                 ),
                 Token::Comma(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 3,
                         character: 17,
+                        offset: 48,
                     },
                     end: LocationData {
                         line: 3,
                         character: 18,
+                        offset: 49,
                     },
                 }),
                 Token::WhiteSpace(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 3,
                         character: 18,
+                        offset: 49,
                     },
                     end: LocationData {
                         line: 3,
                         character: 19,
+                        offset: 50,
                     },
                 }),
                 Token::Text(
                     "https://example".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 3,
                             character: 19,
+                            offset: 50,
                         },
                         end: LocationData {
                             line: 3,
                             character: 34,
+                            offset: 65,
                         },
                     }
                 ),
                 Token::BackSlash(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 3,
                         character: 34,
+                        offset: 65,
                     },
                     end: LocationData {
                         line: 3,
                         character: 35,
+                        offset: 66,
                     },
                 }),
                 Token::Dot(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 3,
                         character: 35,
+                        offset: 66,
                     },
                     end: LocationData {
                         line: 3,
                         character: 36,
+                        offset: 67,
                     },
                 }),
                 Token::Text(
                     "com/".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 3,
                             character: 36,
+                            offset: 67,
                         },
                         end: LocationData {
                             line: 3,
                             character: 40,
+                            offset: 71,
                         },
                     }
                 ),
                 Token::SquareBracketClose(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 3,
                         character: 40,
+                        offset: 71,
                     },
                     end: LocationData {
                         line: 3,
                         character: 41,
+                        offset: 72,
                     },
                 }),
                 Token::NewLine(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 3,
                         character: 41,
+                        offset: 72,
                     },
                     end: LocationData {
                         line: 3,
                         character: 42,
+                        offset: 73,
                     },
                 }),
                 Token::NewLine(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 4,
                         character: 0,
+                        offset: 73,
                     },
                     end: LocationData {
                         line: 4,
                         character: 1,
+                        offset: 74,
                     },
                 }),
                 Token::Text(
                     "2023.12.28".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 5,
                             character: 0,
+                            offset: 74,
                         },
                         end: LocationData {
                             line: 5,
                             character: 10,
+                            offset: 84,
                         },
                     }
                 ),
                 Token::EOF(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 5,
                         character: 10,
+                        offset: 84,
                     },
                     end: LocationData {
                         line: 5,
                         character: 10,
+                        offset: 84,
                     },
                 }),
             ]
@@ -1216,28 +1512,351 @@ mod tests {
                 Token::Text(
                     "こんにちは！🇯🇵".to_string(),
                     Location {
+                        file: FileId::default(),
                         start: LocationData {
                             line: 0,
                             character: 0,
+                            offset: 0,
                         },
                         end: LocationData {
                             line: 0,
                             character: 7,
+                            offset: 26,
                         },
                     }
                 ),
                 Token::EOF(Location {
+                    file: FileId::default(),
                     start: LocationData {
                         line: 0,
                         character: 7,
+                        offset: 26,
                     },
                     end: LocationData {
                         line: 0,
                         character: 7,
+                        offset: 26,
                     },
                 }),
             ]
         );
         Ok(())
     }
+
+    #[test]
+    fn test_split_comments() -> Result<()> {
+        let pwd = std::env::current_dir()?;
+        let uri = pwd
+            .join("test/split_comments.[]")
+            .to_string_lossy()
+            .to_string();
+        let tokens = tokenize(uri.clone())?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Text(
+                    "Hello,".to_string(),
+                    Location {
+                        file: FileId::default(),
+                        start: LocationData {
+                            line: 0,
+                            character: 0,
+                            offset: 0,
+                        },
+                        end: LocationData {
+                            line: 0,
+                            character: 6,
+                            offset: 6,
+                        },
+                    }
+                ),
+                Token::NewLine(Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 0,
+                        character: 6,
+                        offset: 6,
+                    },
+                    end: LocationData {
+                        line: 0,
+                        character: 7,
+                        offset: 7,
+                    },
+                }),
+                Token::Comment(
+                    " this is a comment".to_string(),
+                    Location {
+                        file: FileId::default(),
+                        start: LocationData {
+                            line: 1,
+                            character: 0,
+                            offset: 7,
+                        },
+                        end: LocationData {
+                            line: 1,
+                            character: 20,
+                            offset: 27,
+                        },
+                    }
+                ),
+                Token::NewLine(Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 1,
+                        character: 20,
+                        offset: 27,
+                    },
+                    end: LocationData {
+                        line: 1,
+                        character: 21,
+                        offset: 28,
+                    },
+                }),
+                Token::Text(
+                    "World!".to_string(),
+                    Location {
+                        file: FileId::default(),
+                        start: LocationData {
+                            line: 2,
+                            character: 0,
+                            offset: 28,
+                        },
+                        end: LocationData {
+                            line: 2,
+                            character: 6,
+                            offset: 34,
+                        },
+                    }
+                ),
+                Token::NewLine(Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 2,
+                        character: 6,
+                        offset: 34,
+                    },
+                    end: LocationData {
+                        line: 2,
+                        character: 7,
+                        offset: 35,
+                    },
+                }),
+                Token::BackSlash(Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 3,
+                        character: 0,
+                        offset: 35,
+                    },
+                    end: LocationData {
+                        line: 3,
+                        character: 1,
+                        offset: 36,
+                    },
+                }),
+                Token::Text(
+                    "//not a comment".to_string(),
+                    Location {
+                        file: FileId::default(),
+                        start: LocationData {
+                            line: 3,
+                            character: 1,
+                            offset: 36,
+                        },
+                        end: LocationData {
+                            line: 3,
+                            character: 16,
+                            offset: 51,
+                        },
+                    }
+                ),
+                Token::EOF(Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 3,
+                        character: 16,
+                        offset: 51,
+                    },
+                    end: LocationData {
+                        line: 3,
+                        character: 16,
+                        offset: 51,
+                    },
+                }),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_raw_blocks() -> Result<()> {
+        let pwd = std::env::current_dir()?;
+        let uri = pwd
+            .join("test/split_raw_blocks.[]")
+            .to_string_lossy()
+            .to_string();
+        let tokens = tokenize(uri.clone())?;
+        assert_eq!(
+            tokens,
+            vec![
+                Token::CurlyBracketOpen(Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 0,
+                        character: 0,
+                        offset: 0,
+                    },
+                    end: LocationData {
+                        line: 0,
+                        character: 1,
+                        offset: 1,
+                    },
+                }),
+                Token::Module(
+                    "std".to_string(),
+                    Location {
+                        file: FileId::default(),
+                        start: LocationData {
+                            line: 0,
+                            character: 1,
+                            offset: 1,
+                        },
+                        end: LocationData {
+                            line: 0,
+                            character: 4,
+                            offset: 4,
+                        },
+                    }
+                ),
+                Token::Dot(Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 0,
+                        character: 4,
+                        offset: 4,
+                    },
+                    end: LocationData {
+                        line: 0,
+                        character: 5,
+                        offset: 5,
+                    },
+                }),
+                Token::Ident(
+                    "code".to_string(),
+                    Location {
+                        file: FileId::default(),
+                        start: LocationData {
+                            line: 0,
+                            character: 5,
+                            offset: 5,
+                        },
+                        end: LocationData {
+                            line: 0,
+                            character: 9,
+                            offset: 9,
+                        },
+                    }
+                ),
+                Token::NewLine(Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 0,
+                        character: 9,
+                        offset: 9,
+                    },
+                    end: LocationData {
+                        line: 0,
+                        character: 10,
+                        offset: 10,
+                    },
+                }),
+                Token::Raw(
+                    RawBlock {
+                        fence_length: 3,
+                        info: Some("rust".to_string()),
+                        content: "let a = [1, 2];\n".to_string(),
+                    },
+                    Location {
+                        file: FileId::default(),
+                        start: LocationData {
+                            line: 1,
+                            character: 0,
+                            offset: 10,
+                        },
+                        end: LocationData {
+                            line: 3,
+                            character: 3,
+                            offset: 37,
+                        },
+                    }
+                ),
+                Token::NewLine(Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 3,
+                        character: 3,
+                        offset: 37,
+                    },
+                    end: LocationData {
+                        line: 3,
+                        character: 4,
+                        offset: 38,
+                    },
+                }),
+                Token::CurlyBracketClose(Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 4,
+                        character: 0,
+                        offset: 38,
+                    },
+                    end: LocationData {
+                        line: 4,
+                        character: 1,
+                        offset: 39,
+                    },
+                }),
+                Token::EOF(Location {
+                    file: FileId::default(),
+                    start: LocationData {
+                        line: 4,
+                        character: 1,
+                        offset: 39,
+                    },
+                    end: LocationData {
+                        line: 4,
+                        character: 1,
+                        offset: 39,
+                    },
+                }),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_deeply_nested_commands() -> Result<()> {
+        let depth = 10_000;
+        let text = format!("{}{}", "[std.* ".repeat(depth), "]".repeat(depth));
+        let tokens = tokenize_str(&text)?;
+        // `[`, `std`, `.`, `*`, ` ` for each level, `]` for each level, and EOF
+        assert_eq!(tokens.len(), depth * 6 + 1);
+        assert_eq!(
+            tokens.last(),
+            Some(&Token::EOF(Location {
+                file: FileId::default(),
+                start: LocationData {
+                    line: 0,
+                    character: depth * 8,
+                    offset: depth * 8,
+                },
+                end: LocationData {
+                    line: 0,
+                    character: depth * 8,
+                    offset: depth * 8,
+                },
+            }))
+        );
+        Ok(())
+    }
 }
@@ -1,159 +1,175 @@
-use crate::tokens::Token;
+use unicode_segmentation::UnicodeSegmentation;
 
+use crate::{
+    source_map::FileId,
+    tokens::{Location, LocationData, Token},
+};
+
+/// State of the tokenizer.
+/// It walks the input once with a cursor over its graphemes,
+/// so no state is cloned between steps.
 #[derive(Debug, Default, Clone, PartialEq)]
-pub struct Tokenizer {
-    pub uri: String,
-    pub line: Option<usize>,
-    pub column: Option<usize>,
-    pub token_start_line: Option<usize>,
-    pub token_start_column: Option<usize>,
-    pub untreated: Option<String>,
-    pub pool: Option<String>,
-    pub tokens: Option<Vec<Token>>,
-    pub angle_nest_count: Option<i32>,
-    pub square_nest_count: Option<i32>,
-    pub curly_nest_count: Option<i32>,
-    pub looking_for_identifier: Option<bool>,
+pub struct Tokenizer<'a> {
+    pub file: FileId,
+    pub graphemes: Vec<&'a str>,
+    pub cursor: usize,
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+    pub token_start_line: usize,
+    pub token_start_column: usize,
+    pub token_start_offset: usize,
+    pub pool: String,
+    pub tokens: Vec<Token>,
+    pub angle_nest_count: i32,
+    pub square_nest_count: i32,
+    pub curly_nest_count: i32,
+    /// The nest counts just inside the open curly brackets, innermost last.
+    pub curly_nests: Vec<i32>,
+    /// The nest counts of the curly brackets whose bodies are being tokenized, innermost last.
+    pub body_nests: Vec<i32>,
+    pub looking_for_identifier: bool,
 }
 
-impl Tokenizer {
-    pub fn merge(&self, other: &Tokenizer) -> Tokenizer {
-        Tokenizer {
-            uri: other.uri.clone(),
-            line: match other.line {
-                Some(s) => Some(s),
-                None => self.line,
-            },
-            column: match other.column {
-                Some(s) => Some(s),
-                None => self.column,
-            },
-            token_start_line: match other.token_start_line {
-                Some(s) => Some(s),
-                None => self.token_start_line,
-            },
-            token_start_column: match other.token_start_column {
-                Some(s) => Some(s),
-                None => self.token_start_column,
-            },
-            untreated: match &other.untreated {
-                Some(s) => Some(s.clone()),
-                None => self.untreated.clone(),
-            },
-            pool: match &other.pool {
-                Some(s) => Some(s.clone()),
-                None => self.pool.clone(),
-            },
-            tokens: match &other.tokens {
-                Some(s) => Some(s.clone()),
-                None => self.tokens.clone(),
+impl<'a> Tokenizer<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            graphemes: text.graphemes(true).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the grapheme `n` positions ahead of the cursor,
+    /// or `"\0"` if it is beyond the end of the input.
+    pub fn peek(&self, n: usize) -> &'a str {
+        self.graphemes.get(self.cursor + n).copied().unwrap_or("\0")
+    }
+
+    /// The number of brackets of any kind that are open at the cursor.
+    pub fn nest_count(&self) -> i32 {
+        self.angle_nest_count + self.curly_nest_count + self.square_nest_count
+    }
+
+    /// Whether the cursor is in the arguments of a command rather than in a text.
+    /// The body of a block command is a text like the top level of a document.
+    pub fn in_arguments(&self) -> bool {
+        self.nest_count() > self.body_nests.last().copied().unwrap_or(0)
+    }
+
+    /// Consumes the grapheme under the cursor.
+    pub fn advance(&mut self) {
+        self.offset += self.peek(0).len();
+        self.cursor += 1;
+        self.column += 1;
+    }
+
+    /// Location of the grapheme under the cursor.
+    pub fn head_location(&self) -> Location {
+        Location {
+            file: self.file,
+            start: LocationData {
+                line: self.line,
+                character: self.column,
+                offset: self.offset,
             },
-            angle_nest_count: match other.angle_nest_count {
-                Some(s) => Some(s),
-                None => self.angle_nest_count,
+            end: LocationData {
+                line: self.line,
+                character: self.column + 1,
+                offset: self.offset + self.peek(0).len(),
             },
-            square_nest_count: match other.square_nest_count {
-                Some(s) => Some(s),
-                None => self.square_nest_count,
+        }
+    }
+
+    /// Location from the start of the pooled token to the grapheme under the cursor.
+    pub fn pool_location(&self) -> Location {
+        Location {
+            file: self.file,
+            start: LocationData {
+                line: self.token_start_line,
+                character: self.token_start_column,
+                offset: self.token_start_offset,
             },
-            curly_nest_count: match other.curly_nest_count {
-                Some(s) => Some(s),
-                None => self.curly_nest_count,
+            end: LocationData {
+                line: self.line,
+                character: self.column + 1,
+                offset: self.offset + self.peek(0).len(),
             },
-            looking_for_identifier: match other.looking_for_identifier {
-                Some(s) => Some(s),
-                None => self.looking_for_identifier,
+        }
+    }
+
+    /// Location of the end of the input.
+    pub fn eof_location(&self) -> Location {
+        Location {
+            file: self.file,
+            start: self.cursor_location_data(),
+            end: self.cursor_location_data(),
+        }
+    }
+
+    /// Location from the start of the pooled token to the cursor, excluding the grapheme under it.
+    pub fn span_location(&self) -> Location {
+        Location {
+            file: self.file,
+            start: LocationData {
+                line: self.token_start_line,
+                character: self.token_start_column,
+                offset: self.token_start_offset,
             },
+            end: self.cursor_location_data(),
+        }
+    }
+
+    fn cursor_location_data(&self) -> LocationData {
+        LocationData {
+            line: self.line,
+            character: self.column,
+            offset: self.offset,
         }
     }
+
+    /// Pushes a token consisting of the grapheme under the cursor and consumes it.
+    pub fn push_head(&mut self, token: Token) {
+        self.tokens.push(token);
+        self.advance();
+        self.token_start_column = self.column;
+        self.token_start_offset = self.offset;
+    }
+
+    /// Appends the grapheme under the cursor to the pool and returns the pooled text,
+    /// leaving the pool empty.
+    pub fn take_pool(&mut self) -> String {
+        let head = self.peek(0);
+        self.pool.push_str(head);
+        std::mem::take(&mut self.pool)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tokens::{Location, LocationData};
+    use super::*;
+
+    #[test]
+    fn test_peek_and_advance() {
+        let mut t = Tokenizer::new("a🇯🇵\n");
+        assert_eq!(t.peek(0), "a");
+        assert_eq!(t.peek(1), "🇯🇵");
+        t.advance();
+        assert_eq!(t.peek(0), "🇯🇵");
+        assert_eq!(t.peek(1), "\n");
+        assert_eq!(t.peek(2), "\0");
+        assert_eq!(t.column, 1);
+        assert_eq!(t.offset, 1);
+        t.advance();
+        assert_eq!(t.column, 2);
+        assert_eq!(t.offset, 9);
+    }
 
     #[test]
-    fn test_merge() {
-        use super::*;
-        let a = Tokenizer {
-            uri: "a".to_string(),
-            line: Some(1),
-            column: Some(1),
-            token_start_line: Some(1),
-            token_start_column: Some(1),
-            untreated: Some("a".to_string()),
-            pool: Some("a".to_string()),
-            tokens: Some(vec![Token::Text(
-                "a".to_string(),
-                Location {
-                    start: LocationData {
-                        line: 1,
-                        character: 1,
-                    },
-                    end: LocationData {
-                        line: 1,
-                        character: 1,
-                    },
-                },
-            )]),
-            angle_nest_count: Some(1),
-            square_nest_count: Some(1),
-            curly_nest_count: Some(1),
-            looking_for_identifier: Some(true),
-        };
-        let b = Tokenizer {
-            uri: "b".to_string(),
-            line: Some(2),
-            column: Some(2),
-            token_start_line: Some(2),
-            token_start_column: Some(2),
-            untreated: Some("b".to_string()),
-            pool: Some("b".to_string()),
-            tokens: Some(vec![Token::Text(
-                "b".to_string(),
-                Location {
-                    start: LocationData {
-                        line: 2,
-                        character: 2,
-                    },
-                    end: LocationData {
-                        line: 2,
-                        character: 2,
-                    },
-                },
-            )]),
-            angle_nest_count: Some(2),
-            square_nest_count: Some(2),
-            curly_nest_count: Some(2),
-            looking_for_identifier: Some(false),
-        };
-        let c = a.merge(&b);
-        let res = Tokenizer {
-            uri: "b".to_string(),
-            line: Some(2),
-            column: Some(2),
-            token_start_line: Some(2),
-            token_start_column: Some(2),
-            untreated: Some("b".to_string()),
-            pool: Some("b".to_string()),
-            tokens: Some(vec![Token::Text(
-                "b".to_string(),
-                Location {
-                    start: LocationData {
-                        line: 2,
-                        character: 2,
-                    },
-                    end: LocationData {
-                        line: 2,
-                        character: 2,
-                    },
-                },
-            )]),
-            angle_nest_count: Some(2),
-            square_nest_count: Some(2),
-            curly_nest_count: Some(2),
-            looking_for_identifier: Some(false),
-        };
-        assert_eq!(c, res);
+    fn test_take_pool() {
+        let mut t = Tokenizer::new("ab");
+        t.pool.push('a');
+        t.advance();
+        assert_eq!(t.take_pool(), "ab");
+        assert!(t.pool.is_empty());
     }
 }
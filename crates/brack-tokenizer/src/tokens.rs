@@ -1,10 +1,16 @@
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
+use crate::source_map::FileId;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Token {
     Empty(Location),
     Text(String, Location),
+    Comment(String, Location),
+    /// The front matter with its fences and the newline after it, which is not parsed.
+    FrontMatter(String, Location),
+    Raw(RawBlock, Location),
     Module(String, Location),
     Ident(String, Location),
     NewLine(Location),
@@ -18,30 +24,65 @@ pub enum Token {
     CurlyBracketOpen(Location),
     CurlyBracketClose(Location),
     Comma(Location),
+    Equal(Location),
     EOF(Location),
 }
 
+/// A block fenced by lines of the same number of backticks.
+/// Its content is kept as it is, without parsing commands and escapes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawBlock {
+    pub fence_length: usize,
+    /// Text following the opening fence, e.g. a language name.
+    pub info: Option<String>,
+    /// Lines between the fences, including the newline of the last line.
+    pub content: String,
+}
+
+impl RawBlock {
+    /// Returns the source text of the block including both fences.
+    pub fn source(&self) -> String {
+        let fence = "`".repeat(self.fence_length);
+        format!(
+            "{}{}\n{}{}",
+            fence,
+            self.info.as_deref().unwrap_or_default(),
+            self.content,
+            fence
+        )
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LocationData {
     pub line: usize,
+    /// Column counted in graphemes.
     pub character: usize,
+    /// Byte offset from the beginning of the source text.
+    #[serde(default)]
+    pub offset: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Location {
+    #[serde(default)]
+    pub file: FileId,
     pub start: LocationData,
     pub end: LocationData,
 }
 
 pub fn mock_location() -> Location {
     Location {
+        file: FileId::default(),
         start: LocationData {
             line: 0,
             character: 0,
+            offset: 0,
         },
         end: LocationData {
             line: 0,
             character: 0,
+            offset: 0,
         },
     }
 }
@@ -65,7 +106,11 @@ pub fn merge_location(location1: &Location, location2: &Location) -> Location {
         Ordering::Greater => location1.end.clone(),
     };
 
-    Location { start, end }
+    Location {
+        file: location1.file,
+        start,
+        end,
+    }
 }
 
 impl Token {
@@ -73,6 +118,9 @@ impl Token {
         match self {
             Token::Empty(location) => location.clone(),
             Token::Text(_, location) => location.clone(),
+            Token::Comment(_, location) => location.clone(),
+            Token::FrontMatter(_, location) => location.clone(),
+            Token::Raw(_, location) => location.clone(),
             Token::Module(_, location) => location.clone(),
             Token::Ident(_, location) => location.clone(),
             Token::NewLine(location) => location.clone(),
@@ -86,6 +134,7 @@ impl Token {
             Token::CurlyBracketOpen(location) => location.clone(),
             Token::CurlyBracketClose(location) => location.clone(),
             Token::Comma(location) => location.clone(),
+            Token::Equal(location) => location.clone(),
             Token::EOF(location) => location.clone(),
         }
     }
@@ -94,6 +143,9 @@ impl Token {
         match self {
             Token::Empty(l) => *l = location,
             Token::Text(_, l) => *l = location,
+            Token::Comment(_, l) => *l = location,
+            Token::FrontMatter(_, l) => *l = location,
+            Token::Raw(_, l) => *l = location,
             Token::Module(_, l) => *l = location,
             Token::Ident(_, l) => *l = location,
             Token::NewLine(l) => *l = location,
@@ -107,6 +159,7 @@ impl Token {
             Token::CurlyBracketOpen(l) => *l = location,
             Token::CurlyBracketClose(l) => *l = location,
             Token::Comma(l) => *l = location,
+            Token::Equal(l) => *l = location,
             Token::EOF(l) => *l = location,
         }
     }
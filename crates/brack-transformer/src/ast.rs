@@ -1,6 +1,7 @@
 use std::fmt;
 
-use brack_tokenizer::tokens::{merge_location, Location};
+use anyhow::Result;
+use brack_tokenizer::tokens::{merge_location, Location, RawBlock};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -18,6 +19,13 @@ pub struct LeafNode {
     pub location: Location,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct RawNode {
+    pub id: String,
+    pub raw: RawBlock,
+    pub location: Location,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub enum AST {
     Document(InnerNode),
@@ -26,9 +34,13 @@ pub enum AST {
     Angle(InnerNode),
     Square(InnerNode),
     Curly(InnerNode),
+    /// The statements in the body of a block command.
+    Block(InnerNode),
+    Named(InnerNode),
     Ident(LeafNode),
     Module(LeafNode),
     Text(LeafNode),
+    Raw(RawNode),
     Invalid(LeafNode),
     Ignored(LeafNode),
 }
@@ -40,36 +52,56 @@ impl fmt::Display for AST {
 }
 
 impl AST {
-    pub fn children(&self) -> &Vec<AST> {
+    /// Returns the children of an inner node, or an error for leaf, invalid and ignored nodes.
+    pub fn try_children(&self) -> Result<&Vec<AST>> {
         match self {
             AST::Document(node)
             | AST::Stmt(node)
             | AST::Expr(node)
             | AST::Angle(node)
             | AST::Square(node)
-            | AST::Curly(node) => &node.children,
-            AST::Ident(_) | AST::Module(_) | AST::Text(_) => {
-                panic!("Leaf node has no children: {}", self)
+            | AST::Curly(node)
+            | AST::Block(node)
+            | AST::Named(node) => Ok(&node.children),
+            AST::Ident(_) | AST::Module(_) | AST::Text(_) | AST::Raw(_) => {
+                anyhow::bail!("Leaf node has no children: {}", self)
             }
-            AST::Invalid(_) => panic!("This node is broken"),
-            AST::Ignored(_) => panic!("This node has to be ignored"),
+            AST::Invalid(_) => anyhow::bail!("This node is broken"),
+            AST::Ignored(_) => anyhow::bail!("This node has to be ignored"),
         }
     }
 
-    pub fn value(&self) -> Option<String> {
+    /// Returns the value of a leaf node, or an error for inner nodes and leaves without a value.
+    pub fn try_value(&self) -> Result<String> {
         match self {
-            AST::Ident(leaf) | AST::Module(leaf) | AST::Text(leaf) => leaf.value.clone(),
+            AST::Ident(leaf) | AST::Module(leaf) | AST::Text(leaf) => leaf
+                .value
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("No value found")),
+            AST::Raw(raw) => Ok(raw.raw.content.clone()),
             AST::Document(_)
             | AST::Stmt(_)
             | AST::Expr(_)
             | AST::Angle(_)
             | AST::Square(_)
-            | AST::Curly(_) => panic!("Inner node has no value"),
-            AST::Invalid(_) => panic!("This node is broken"),
-            AST::Ignored(_) => panic!("This node has to be ignored"),
+            | AST::Curly(_)
+            | AST::Block(_)
+            | AST::Named(_) => anyhow::bail!("Inner node has no value"),
+            AST::Invalid(_) => anyhow::bail!("This node is broken"),
+            AST::Ignored(_) => anyhow::bail!("This node has to be ignored"),
         }
     }
 
+    /// Returns the children of an inner node, or `None` for leaf, invalid and ignored nodes.
+    pub fn children(&self) -> Option<&Vec<AST>> {
+        self.try_children().ok()
+    }
+
+    /// Returns the value of a leaf node, or `None` for inner nodes and leaves without a value.
+    pub fn value(&self) -> Option<String> {
+        self.try_value().ok()
+    }
+
     pub fn id(&self) -> String {
         match self {
             AST::Document(node)
@@ -77,44 +109,57 @@ impl AST {
             | AST::Expr(node)
             | AST::Angle(node)
             | AST::Square(node)
-            | AST::Curly(node) => node.id.clone(),
+            | AST::Curly(node)
+            | AST::Block(node)
+            | AST::Named(node) => node.id.clone(),
             AST::Ident(leaf)
             | AST::Module(leaf)
             | AST::Text(leaf)
             | AST::Invalid(leaf)
             | AST::Ignored(leaf) => leaf.id.clone(),
+            AST::Raw(raw) => raw.id.clone(),
+        }
+    }
+
+    pub fn location(&self) -> Location {
+        match self {
+            AST::Document(node)
+            | AST::Stmt(node)
+            | AST::Expr(node)
+            | AST::Angle(node)
+            | AST::Square(node)
+            | AST::Curly(node)
+            | AST::Block(node)
+            | AST::Named(node) => node.location.clone(),
+            AST::Ident(leaf)
+            | AST::Module(leaf)
+            | AST::Text(leaf)
+            | AST::Invalid(leaf)
+            | AST::Ignored(leaf) => leaf.location.clone(),
+            AST::Raw(raw) => raw.location.clone(),
         }
     }
 
-    pub fn add(&mut self, ast: AST) {
+    /// Adds a child to an inner node, or returns an error for leaf, invalid and ignored nodes.
+    pub fn add(&mut self, ast: AST) -> Result<()> {
         match self {
             AST::Document(node)
             | AST::Stmt(node)
             | AST::Expr(node)
             | AST::Angle(node)
             | AST::Square(node)
-            | AST::Curly(node) => {
-                node.children.push(ast.clone());
-                let location_children = match ast {
-                    AST::Document(inner)
-                    | AST::Stmt(inner)
-                    | AST::Expr(inner)
-                    | AST::Angle(inner)
-                    | AST::Square(inner)
-                    | AST::Curly(inner) => inner.location,
-                    AST::Text(leaf)
-                    | AST::Ident(leaf)
-                    | AST::Module(leaf)
-                    | AST::Invalid(leaf)
-                    | AST::Ignored(leaf) => leaf.location,
-                };
-                node.location = merge_location(&node.location, &location_children);
+            | AST::Curly(node)
+            | AST::Block(node)
+            | AST::Named(node) => {
+                node.location = merge_location(&node.location, &ast.location());
+                node.children.push(ast);
+                Ok(())
             }
-            AST::Ident(_) | AST::Module(_) | AST::Text(_) => {
-                panic!("Cannot add child to leaf node");
+            AST::Ident(_) | AST::Module(_) | AST::Text(_) | AST::Raw(_) => {
+                anyhow::bail!("Cannot add child to leaf node")
             }
-            AST::Invalid(_) => panic!("This node is broken"),
-            AST::Ignored(_) => panic!("This node has to be ignored"),
+            AST::Invalid(_) => anyhow::bail!("This node is broken"),
+            AST::Ignored(_) => anyhow::bail!("This node has to be ignored"),
         }
     }
 
@@ -125,7 +170,9 @@ impl AST {
             | AST::Expr(node)
             | AST::Angle(node)
             | AST::Square(node)
-            | AST::Curly(node) => {
+            | AST::Curly(node)
+            | AST::Block(node)
+            | AST::Named(node) => {
                 if node.id == id {
                     return Some(self);
                 }
@@ -146,6 +193,12 @@ impl AST {
                 }
                 None
             }
+            AST::Raw(raw) => {
+                if raw.id == id {
+                    return Some(self);
+                }
+                None
+            }
         }
     }
 
@@ -194,11 +247,41 @@ impl AST {
                 }
                 Ok(())
             }
-            AST::Ident(node) => writeln!(f, "{}Ident: {}", ident_str, node.value.as_ref().unwrap()),
+            AST::Block(node) => {
+                writeln!(f, "{}Block", ident_str)?;
+                for child in &node.children {
+                    child.display_with_ident(f, ident + 1)?;
+                }
+                Ok(())
+            }
+            AST::Named(node) => {
+                writeln!(f, "{}Named", ident_str)?;
+                for child in &node.children {
+                    child.display_with_ident(f, ident + 1)?;
+                }
+                Ok(())
+            }
+            AST::Ident(node) => writeln!(
+                f,
+                "{}Ident: {}",
+                ident_str,
+                node.value.as_deref().unwrap_or_default()
+            ),
             AST::Module(node) => {
-                writeln!(f, "{}Module: {}", ident_str, node.value.as_ref().unwrap())
+                writeln!(
+                    f,
+                    "{}Module: {}",
+                    ident_str,
+                    node.value.as_deref().unwrap_or_default()
+                )
             }
-            AST::Text(node) => writeln!(f, "{}Text: {}", ident_str, node.value.as_ref().unwrap()),
+            AST::Text(node) => writeln!(
+                f,
+                "{}Text: {}",
+                ident_str,
+                node.value.as_deref().unwrap_or_default()
+            ),
+            AST::Raw(raw) => writeln!(f, "{}Raw: {:?}", ident_str, raw.raw.content),
             AST::Invalid(_) => writeln!(f, "{}Invalid", ident_str),
             AST::Ignored(_) => writeln!(f, "{}Ignored", ident_str),
         }
@@ -253,6 +336,23 @@ pub fn new_curly(children: Vec<AST>, location: Location) -> AST {
     })
 }
 
+pub fn new_block(children: Vec<AST>, location: Location) -> AST {
+    AST::Block(InnerNode {
+        id: Uuid::new_v4().to_string(),
+        children,
+        location,
+    })
+}
+
+/// Creates a named argument `name=value` from its name and value.
+pub fn new_named(name: AST, value: AST, location: Location) -> AST {
+    AST::Named(InnerNode {
+        id: Uuid::new_v4().to_string(),
+        children: vec![name, value],
+        location,
+    })
+}
+
 pub fn new_ident(value: Option<String>, location: Location) -> AST {
     AST::Ident(LeafNode {
         id: Uuid::new_v4().to_string(),
@@ -277,6 +377,14 @@ pub fn new_text(value: Option<String>, location: Location) -> AST {
     })
 }
 
+pub fn new_raw(raw: RawBlock, location: Location) -> AST {
+    AST::Raw(RawNode {
+        id: Uuid::new_v4().to_string(),
+        raw,
+        location,
+    })
+}
+
 pub fn new_invalid(location: Location) -> AST {
     AST::Invalid(LeafNode {
         id: Uuid::new_v4().to_string(),
@@ -304,9 +412,12 @@ pub fn assert_ast_eq(node1: &AST, node2: &AST) {
         (AST::Angle(inner1), AST::Angle(inner2)) => assert_inner_node_eq(inner1, inner2),
         (AST::Square(inner1), AST::Square(inner2)) => assert_inner_node_eq(inner1, inner2),
         (AST::Curly(inner1), AST::Curly(inner2)) => assert_inner_node_eq(inner1, inner2),
+        (AST::Block(inner1), AST::Block(inner2)) => assert_inner_node_eq(inner1, inner2),
+        (AST::Named(inner1), AST::Named(inner2)) => assert_inner_node_eq(inner1, inner2),
         (AST::Ident(leaf1), AST::Ident(leaf2)) => assert_leaf_node_eq(leaf1, leaf2),
         (AST::Module(leaf1), AST::Module(leaf2)) => assert_leaf_node_eq(leaf1, leaf2),
         (AST::Text(leaf1), AST::Text(leaf2)) => assert_leaf_node_eq(leaf1, leaf2),
+        (AST::Raw(raw1), AST::Raw(raw2)) => assert_eq!(raw1.raw, raw2.raw),
         _ => panic!(
             "Mismatched AST node types or unexpected AST node\nleft: {:?}\nright: {:?}",
             node1, node2
@@ -322,11 +433,44 @@ pub fn matches_kind(node1: &AST, node2: &AST) -> bool {
             | (AST::Expr(_), AST::Expr(_))
             | (AST::Angle(_), AST::Angle(_))
             | (AST::Curly(_), AST::Curly(_))
+            | (AST::Block(_), AST::Block(_))
             | (AST::Square(_), AST::Square(_))
+            | (AST::Named(_), AST::Named(_))
             | (AST::Module(_), AST::Module(_))
             | (AST::Ident(_), AST::Ident(_))
             | (AST::Text(_), AST::Text(_))
+            | (AST::Raw(_), AST::Raw(_))
             | (AST::Invalid(_), AST::Invalid(_))
             | (AST::Ignored(_), AST::Ignored(_))
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use brack_tokenizer::tokens::mock_location;
+
+    use super::{new_expr, new_invalid, new_text};
+
+    #[test]
+    fn test_accessors_do_not_panic() {
+        let text = new_text(Some("Hello".to_string()), mock_location());
+        assert!(text.try_children().is_err());
+        assert_eq!(text.try_value().ok(), Some("Hello".to_string()));
+
+        let expr = new_expr(vec![text], mock_location());
+        assert_eq!(
+            expr.try_children().map(|children| children.len()).ok(),
+            Some(1)
+        );
+        assert!(expr.try_value().is_err());
+
+        assert_eq!(expr.children().map(|children| children.len()), Some(1));
+        assert_eq!(expr.value(), None);
+
+        let mut invalid = new_invalid(mock_location());
+        assert!(invalid.try_children().is_err());
+        assert!(invalid.try_value().is_err());
+        assert!(invalid.children().is_none());
+        assert!(invalid.add(expr).is_err());
+    }
+}
@@ -5,14 +5,18 @@ use crate::error::TransformError;
 pub fn simplify(cst: &CST) -> (CST, Vec<TransformError>) {
     let node = match cst {
         CST::BackSlash(node) => node,
-        _ => panic!("Cannot pass non-back-slash node to backslash::simplify"),
+        _ => {
+            return (
+                cst.clone(),
+                vec![TransformError::MalformedTree(cst.location())],
+            )
+        }
     };
-    let mut errors = vec![];
-
-    if node.children.is_empty() {
-        errors.push(TransformError::InvalidBackslash(node.location.clone()));
-        return (new_invalid(node.location.clone()), errors);
+    match node.children.first() {
+        Some(escaped) => (escaped.clone(), vec![]),
+        None => (
+            new_invalid("\\".to_string(), node.location.clone()),
+            vec![TransformError::InvalidBackslash(node.location.clone())],
+        ),
     }
-
-    (cst.children()[0].clone(), errors)
 }
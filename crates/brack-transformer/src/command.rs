@@ -0,0 +1,185 @@
+use anyhow::Result;
+use brack_tokenizer::tokens::Location;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ast::AST,
+    visit::{walk_ast, Visit},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommandKind {
+    /// `<module.ident ...>`, expanded by the macro expander.
+    Angle,
+    /// `[module.ident ...]`, an inline command.
+    Square,
+    /// `{module.ident ...}`, a block command.
+    Curly,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Arg {
+    /// The name of a named argument such as `url=...`.
+    pub name: Option<String>,
+    pub value: AST,
+    pub location: Location,
+}
+
+impl Arg {
+    /// Whether the argument is the body of a block command.
+    pub fn is_body(&self) -> bool {
+        matches!(self.value, AST::Block(_))
+    }
+}
+
+/// A command node whose module, identifier and arguments are already resolved,
+/// so that consumers do not need to check the shape of the children again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandCall {
+    pub id: String,
+    pub kind: CommandKind,
+    pub module: String,
+    pub ident: String,
+    pub args: Vec<Arg>,
+    pub location: Location,
+}
+
+impl CommandCall {
+    /// Resolves an angle, square or curly node.
+    /// The children must be a module, an identifier and then the arguments.
+    pub fn resolve(ast: &AST) -> Result<Self> {
+        let (kind, node) = match ast {
+            AST::Angle(node) => (CommandKind::Angle, node),
+            AST::Square(node) => (CommandKind::Square, node),
+            AST::Curly(node) => (CommandKind::Curly, node),
+            _ => anyhow::bail!("Only angle, square and curly nodes are commands"),
+        };
+        let (module, ident, args) = match &node.children[..] {
+            [AST::Module(module), AST::Ident(ident), args @ ..] => (module, ident, args),
+            _ => anyhow::bail!("{:?} must contain module and identifier", kind),
+        };
+        let module = module
+            .value
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Module name must be a string"))?;
+        let ident = ident
+            .value
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Identifier name must be a string"))?;
+        let args = args.iter().map(resolve_arg).collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            id: node.id.clone(),
+            kind,
+            module,
+            ident,
+            args,
+            location: node.location.clone(),
+        })
+    }
+}
+
+impl TryFrom<&AST> for CommandCall {
+    type Error = anyhow::Error;
+
+    fn try_from(ast: &AST) -> Result<Self> {
+        Self::resolve(ast)
+    }
+}
+
+fn resolve_arg(ast: &AST) -> Result<Arg> {
+    match ast {
+        AST::Named(node) => match &node.children[..] {
+            [AST::Ident(name), value] => Ok(Arg {
+                name: Some(
+                    name.value
+                        .clone()
+                        .ok_or_else(|| anyhow::anyhow!("Named argument must have a name"))?,
+                ),
+                value: value.clone(),
+                location: node.location.clone(),
+            }),
+            _ => anyhow::bail!("Named argument must have a name and a value"),
+        },
+        _ => Ok(Arg {
+            name: None,
+            value: ast.clone(),
+            location: ast.location(),
+        }),
+    }
+}
+
+/// Resolves all commands in the AST, including ones nested in arguments, in document order.
+pub fn collect_commands(ast: &AST) -> Result<Vec<CommandCall>> {
+    let mut collector = Collector {
+        commands: vec![],
+        error: None,
+    };
+    collector.visit_ast(ast);
+    match collector.error {
+        Some(error) => Err(error),
+        None => Ok(collector.commands),
+    }
+}
+
+struct Collector {
+    commands: Vec<CommandCall>,
+    error: Option<anyhow::Error>,
+}
+
+impl Visit for Collector {
+    fn visit_ast(&mut self, ast: &AST) {
+        if let AST::Angle(_) | AST::Square(_) | AST::Curly(_) = ast {
+            match CommandCall::resolve(ast) {
+                Ok(command) => self.commands.push(command),
+                Err(error) => {
+                    self.error.get_or_insert(error);
+                }
+            }
+        }
+        walk_ast(self, ast);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_parser::parse::parse;
+    use brack_tokenizer::tokenize::tokenize_str;
+
+    use crate::{ast::new_text, transform::transform};
+
+    use super::{collect_commands, CommandKind};
+
+    #[test]
+    fn test_collect_commands() -> Result<()> {
+        let tokens =
+            tokenize_str("{std.# [std.* Title]}\n\n[std.@ Brack, url=https://example\\.com]")?;
+        let (cst, _) = parse(&tokens);
+        let (ast, _) = transform(&cst);
+        let commands = collect_commands(&ast)?;
+        let names = commands
+            .iter()
+            .map(|command| {
+                (
+                    command.kind,
+                    command.module.as_str(),
+                    command.ident.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                (CommandKind::Curly, "std", "#"),
+                (CommandKind::Square, "std", "*"),
+                (CommandKind::Square, "std", "@"),
+            ]
+        );
+        let args = &commands[2].args;
+        assert_eq!(args.len(), 2);
+        assert_eq!(args[0].name, None);
+        assert_eq!(args[1].name.as_deref(), Some("url"));
+        assert!(collect_commands(&new_text(None, commands[0].location.clone()))?.is_empty());
+        Ok(())
+    }
+}
@@ -1,42 +1,105 @@
 use brack_parser::cst::{InnerNode, CST};
-use brack_tokenizer::tokens::merge_location;
+use brack_tokenizer::tokens::{merge_location, mock_location, Location};
 
 use crate::{
     error::TransformError,
     simplify,
     utils::{
         check_if_dot, check_if_ident_or_angle_bracket, check_if_module_or_angle_bracket,
-        check_unexpected_dot, check_valid_arguments, remove_elements_not_included_ast,
-        remove_whitespaces_and_newlines,
+        check_unexpected_dot, check_valid_arguments, collapse_whitespaces_in_arguments,
+        remove_elements_not_included_ast, resolve_equals_in_arguments,
     },
 };
 
-fn check_if_the_first_and_last_node_are_brackets(csts: &[CST]) -> Vec<TransformError> {
+fn check_if_the_first_and_last_node_are_brackets(
+    csts: &[CST],
+    location: &Location,
+) -> Vec<TransformError> {
     let mut errors = vec![];
-    match (csts[0].clone(), csts[csts.len() - 1].clone()) {
-        (CST::CurlyBracketOpen(_), CST::CurlyBracketClose(_)) => (),
-        (CST::CurlyBracketOpen(left), CST::AngleBracketClose(right))
-        | (CST::CurlyBracketOpen(left), CST::SquareBracketClose(right)) => errors.push(
+    match (csts.first(), csts.last()) {
+        (Some(CST::CurlyBracketOpen(_)), Some(CST::CurlyBracketClose(_))) => (),
+        (Some(CST::CurlyBracketOpen(left)), Some(CST::AngleBracketClose(right)))
+        | (Some(CST::CurlyBracketOpen(left)), Some(CST::SquareBracketClose(right))) => errors.push(
             TransformError::MismatchedBracket(merge_location(&left.location, &right.location)),
         ),
-        (CST::CurlyBracketOpen(left), right) => errors.push(TransformError::CurlyNotClosed(
-            merge_location(&left.location, &right.location()),
-        )),
-        _ => panic!(
-            "Maybe cst parser is broken because CST::Curly must have bracket-open node first."
+        (Some(CST::CurlyBracketOpen(left)), Some(right)) => errors.push(
+            TransformError::CurlyNotClosed(merge_location(&left.location, &right.location())),
         ),
+        // CST::Curly must have a bracket-open node first.
+        _ => errors.push(TransformError::MalformedTree(location.clone())),
     }
     errors
 }
 
+/// Splits the children of a curly bracket into its header and the statements of its body.
+/// The body starts at the first newline that does not follow a trailing comma,
+/// and its statements are separated by blank lines.
+/// The closing bracket is kept at the end of the header.
+fn split_body(children: &[CST]) -> (Vec<CST>, Vec<CST>) {
+    let mut children = children.to_vec();
+    let close = match children.last() {
+        Some(CST::Expr(_)) | Some(CST::Newline(_)) | None => None,
+        Some(_) => children.pop(),
+    };
+    let Some(start) = children.iter().enumerate().position(|(i, child)| {
+        matches!(child, CST::Newline(_)) && !ends_with_comma(&children[..i])
+    }) else {
+        children.extend(close);
+        return (children, vec![]);
+    };
+    let lines = children.split_off(start);
+    children.extend(close);
+
+    let mut stmts = vec![];
+    let mut stmt = InnerNode::new(mock_location());
+    let mut previous_newline = false;
+    for line in lines {
+        let newline = matches!(line, CST::Newline(_));
+        if newline && previous_newline && !stmt.children.is_empty() {
+            let node = std::mem::replace(&mut stmt, InnerNode::new(mock_location()));
+            stmts.push(CST::Stmt(node));
+        }
+        if !newline {
+            if stmt.children.is_empty() {
+                stmt.location = line.location();
+            }
+            stmt.add(line);
+        }
+        previous_newline = newline;
+    }
+    if !stmt.children.is_empty() {
+        stmts.push(CST::Stmt(stmt));
+    }
+    (children, stmts)
+}
+
+/// Whether the last node apart from whitespace is a comma, looking into expressions.
+fn ends_with_comma(csts: &[CST]) -> bool {
+    match csts
+        .iter()
+        .rev()
+        .find(|cst| !matches!(cst, CST::Whitespace(_)))
+    {
+        Some(CST::Comma(_)) => true,
+        Some(CST::Expr(node)) => ends_with_comma(&node.children),
+        _ => false,
+    }
+}
+
 pub fn simplify(cst: &CST) -> (CST, Vec<TransformError>) {
     let node = match cst {
         CST::Curly(node) => node,
-        _ => panic!("Cannot pass non-curly-bracket node to curly::simplify"),
+        _ => {
+            return (
+                cst.clone(),
+                vec![TransformError::MalformedTree(cst.location())],
+            )
+        }
     };
     let mut errors = vec![];
+    let (header, body) = split_body(&node.children);
     let mut csts = vec![];
-    for child in node.children.clone() {
+    for child in header {
         let (cst, mut node_errors) = simplify::simplify(&child);
         let nodes = match cst {
             CST::Expr(node) => node.children.clone(),
@@ -46,15 +109,26 @@ pub fn simplify(cst: &CST) -> (CST, Vec<TransformError>) {
         errors.append(&mut node_errors);
     }
 
-    errors.append(&mut check_if_the_first_and_last_node_are_brackets(&csts));
+    errors.append(&mut check_if_the_first_and_last_node_are_brackets(
+        &csts,
+        &node.location,
+    ));
     errors.append(&mut check_if_module_or_angle_bracket(&csts));
     errors.append(&mut check_if_dot(&csts));
     errors.append(&mut check_if_ident_or_angle_bracket(&csts));
     errors.append(&mut check_unexpected_dot(&csts));
-    let csts = remove_whitespaces_and_newlines(&csts);
+    let csts = collapse_whitespaces_in_arguments(&csts);
     let (csts, mut new_errors) = check_valid_arguments(&csts);
     errors.append(&mut new_errors);
-    let csts = remove_elements_not_included_ast(&csts);
+    let csts = resolve_equals_in_arguments(&csts);
+    let mut csts = remove_elements_not_included_ast(&csts);
+    for stmt in body {
+        let (stmt, mut stmt_errors) = simplify::simplify(&stmt);
+        errors.append(&mut stmt_errors);
+        if !matches!(&stmt, CST::Stmt(node) if node.children.is_empty()) {
+            csts.push(stmt);
+        }
+    }
 
     (
         CST::Curly(InnerNode {
@@ -65,3 +139,66 @@ pub fn simplify(cst: &CST) -> (CST, Vec<TransformError>) {
         errors,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_parser::parse::parse;
+    use brack_tokenizer::tokenize::tokenize_str;
+
+    use crate::{ast::AST, transform::transform, unparse::unparse};
+
+    #[test]
+    fn test_curly_with_body() -> Result<()> {
+        let tokens = tokenize_str(
+            "{std.callout note\nHello, world.\n// a comment\nSecond line.\n\n{std.column\nNested.\n}\n}",
+        )?;
+        let (cst, errors) = parse(&tokens);
+        assert!(errors.is_empty());
+        let (ast, errors) = transform(&cst);
+        assert!(errors.is_empty());
+
+        let curly = &ast.try_children()?[0].try_children()?[0].try_children()?[0];
+        let [AST::Module(_), AST::Ident(_), AST::Expr(_), AST::Block(block)] =
+            &curly.try_children()?[..]
+        else {
+            panic!("Unexpected children of a curly\n{}", curly);
+        };
+        assert_eq!(block.children.len(), 2);
+        assert_eq!(block.children[0].try_children()?.len(), 2);
+        assert_eq!(unparse(&block.children[1]), "{std.column\nNested.\n}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_curly_with_multi_line_arguments() -> Result<()> {
+        let tokens =
+            tokenize_str("{std.code a,\nb}\n\n{std.callout note,\n  title=Tips\nBody.\n}")?;
+        let (cst, errors) = parse(&tokens);
+        assert!(errors.is_empty());
+        let (ast, errors) = transform(&cst);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let code = &ast.try_children()?[0].try_children()?[0].try_children()?[0];
+        let [AST::Module(_), AST::Ident(_), AST::Expr(a), AST::Expr(b)] = &code.try_children()?[..]
+        else {
+            panic!("Unexpected children of a curly\n{}", code);
+        };
+        assert_eq!(
+            (
+                unparse(&AST::Expr(a.clone())),
+                unparse(&AST::Expr(b.clone()))
+            ),
+            ("a".to_string(), "b".to_string())
+        );
+
+        let callout = &ast.try_children()?[1].try_children()?[0].try_children()?[0];
+        let [AST::Module(_), AST::Ident(_), AST::Expr(_), AST::Named(_), AST::Block(block)] =
+            &callout.try_children()?[..]
+        else {
+            panic!("Unexpected children of a curly\n{}", callout);
+        };
+        assert_eq!(unparse(&AST::Block(block.clone())), "Body.");
+        Ok(())
+    }
+}
@@ -6,7 +6,12 @@ use crate::{error::TransformError, simplify, utils::remove_elements_not_included
 pub fn simplify(cst: &CST) -> (CST, Vec<TransformError>) {
     let node = match cst {
         CST::Document(node) => node,
-        _ => panic!("Cannot pass non-document node to document::simplify"),
+        _ => {
+            return (
+                cst.clone(),
+                vec![TransformError::MalformedTree(cst.location())],
+            )
+        }
     };
     let mut errors = vec![];
     let mut csts = vec![];
@@ -18,6 +23,8 @@ pub fn simplify(cst: &CST) -> (CST, Vec<TransformError>) {
     }
 
     csts = remove_elements_not_included_ast(&csts);
+    // Statements that consisted only of comments become empty and are dropped.
+    csts.retain(|cst| !matches!(cst, CST::Stmt(node) if node.children.is_empty()));
 
     (
         CST::Document(InnerNode {
@@ -1,6 +1,6 @@
 use std::fmt::{self, Display, Formatter};
 
-use brack_tokenizer::tokens::Location;
+use brack_tokenizer::{diagnostic::Diagnostic, tokens::Location};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -19,6 +19,7 @@ pub enum TransformError {
     UnexpectedDot(Location),
     UnexpectedComma(Location),
     InvalidBackslash(Location),
+    MalformedTree(Location),
 }
 
 impl TransformError {
@@ -38,6 +39,7 @@ impl TransformError {
             Self::UnexpectedDot(location) => location.clone(),
             Self::UnexpectedComma(location) => location.clone(),
             Self::InvalidBackslash(location) => location.clone(),
+            Self::MalformedTree(location) => location.clone(),
         }
     }
 
@@ -59,6 +61,64 @@ impl TransformError {
             Self::InvalidBackslash(_) => {
                 "Backslash must be followed by dot, comma, backslash, or bracket".to_string()
             }
+            Self::MalformedTree(_) => "Malformed syntax tree".to_string(),
+        }
+    }
+}
+
+impl TransformError {
+    pub fn get_code(&self) -> &'static str {
+        match self {
+            Self::AngleNotOpened(_) => "B0002",
+            Self::AngleNotClosed(_) => "B0003",
+            Self::CurlyNotOpened(_) => "B0004",
+            Self::CurlyNotClosed(_) => "B0005",
+            Self::SquareNotOpened(_) => "B0006",
+            Self::SquareNotClosed(_) => "B0007",
+            Self::MismatchedBracket(_) => "B0008",
+            Self::ModuleNotFound(_) => "B0009",
+            Self::IdentifierNotFound(_) => "B0010",
+            Self::DotNotFound(_) => "B0011",
+            Self::CommaNotFound(_) => "B0012",
+            Self::UnexpectedDot(_) => "B0013",
+            Self::UnexpectedComma(_) => "B0014",
+            Self::InvalidBackslash(_) => "B0015",
+            Self::MalformedTree(_) => "B0016",
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let location = self.get_location();
+        let diagnostic = Diagnostic::error(self.get_code(), self.get_message(), location.clone());
+        let end = Location {
+            file: location.file,
+            start: location.end.clone(),
+            end: location.end.clone(),
+        };
+        match self {
+            Self::AngleNotClosed(_) => diagnostic.with_fix("Insert `>`", end, ">"),
+            Self::CurlyNotClosed(_) => diagnostic.with_fix("Insert `}`", end, "}"),
+            Self::SquareNotClosed(_) => diagnostic.with_fix("Insert `]`", end, "]"),
+            Self::AngleNotOpened(_) | Self::CurlyNotOpened(_) | Self::SquareNotOpened(_) => {
+                diagnostic.with_help("write a backslash before the bracket to use it as text")
+            }
+            Self::MismatchedBracket(_) => {
+                diagnostic.with_help("close the bracket with the same kind of bracket")
+            }
+            Self::ModuleNotFound(_) | Self::IdentifierNotFound(_) | Self::DotNotFound(_) => {
+                diagnostic.with_help("a command is called as `[module.ident arguments]`")
+            }
+            Self::CommaNotFound(_) => diagnostic.with_help("separate arguments with commas"),
+            Self::UnexpectedDot(_) => diagnostic
+                .with_help("write `\\.` to use a dot as text")
+                .with_fix("Escape the dot", location, "\\."),
+            Self::UnexpectedComma(_) => diagnostic.with_fix("Remove the comma", location, ""),
+            Self::InvalidBackslash(_) => diagnostic
+                .with_help("write `\\\\` to use a backslash as text")
+                .with_fix("Escape the backslash", location, "\\\\"),
+            Self::MalformedTree(_) => {
+                diagnostic.with_help("the parser produced an unexpected tree; please report this")
+            }
         }
     }
 }
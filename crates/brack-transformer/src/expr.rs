@@ -6,12 +6,20 @@ use crate::{error::TransformError, simplify};
 pub fn simplify(cst: &CST) -> (CST, Vec<TransformError>) {
     let node = match cst {
         CST::Expr(node) => node,
-        _ => panic!("Cannot pass non-expr node to expr::simplify"),
+        _ => {
+            return (
+                cst.clone(),
+                vec![TransformError::MalformedTree(cst.location())],
+            )
+        }
     };
     let mut errors = vec![];
     let mut csts = vec![];
 
     for child in node.children.clone() {
+        if let CST::Comment(_) = child {
+            continue;
+        }
         let (cst, mut node_errors) = simplify::simplify(&child);
         csts.push(cst);
         errors.append(&mut node_errors);
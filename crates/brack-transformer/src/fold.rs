@@ -0,0 +1,141 @@
+use crate::ast::{InnerNode, LeafNode, RawNode, AST};
+
+/// Rebuilds an AST by taking the ownership of each node.
+/// Unlike `VisitMut`, a method may replace the node with another kind of node.
+/// Each method folds the children and keeps the node as it is by default.
+pub trait Fold {
+    fn fold_ast(&mut self, ast: AST) -> AST {
+        walk_ast(self, ast)
+    }
+
+    fn fold_document(&mut self, node: InnerNode) -> AST {
+        AST::Document(walk_inner(self, node))
+    }
+
+    fn fold_stmt(&mut self, node: InnerNode) -> AST {
+        AST::Stmt(walk_inner(self, node))
+    }
+
+    fn fold_expr(&mut self, node: InnerNode) -> AST {
+        AST::Expr(walk_inner(self, node))
+    }
+
+    fn fold_angle(&mut self, node: InnerNode) -> AST {
+        AST::Angle(walk_inner(self, node))
+    }
+
+    fn fold_square(&mut self, node: InnerNode) -> AST {
+        AST::Square(walk_inner(self, node))
+    }
+
+    fn fold_curly(&mut self, node: InnerNode) -> AST {
+        AST::Curly(walk_inner(self, node))
+    }
+
+    fn fold_block(&mut self, node: InnerNode) -> AST {
+        AST::Block(walk_inner(self, node))
+    }
+
+    fn fold_named(&mut self, node: InnerNode) -> AST {
+        AST::Named(walk_inner(self, node))
+    }
+
+    fn fold_ident(&mut self, leaf: LeafNode) -> AST {
+        AST::Ident(leaf)
+    }
+
+    fn fold_module(&mut self, leaf: LeafNode) -> AST {
+        AST::Module(leaf)
+    }
+
+    fn fold_text(&mut self, leaf: LeafNode) -> AST {
+        AST::Text(leaf)
+    }
+
+    fn fold_raw(&mut self, raw: RawNode) -> AST {
+        AST::Raw(raw)
+    }
+
+    fn fold_invalid(&mut self, leaf: LeafNode) -> AST {
+        AST::Invalid(leaf)
+    }
+
+    fn fold_ignored(&mut self, leaf: LeafNode) -> AST {
+        AST::Ignored(leaf)
+    }
+}
+
+pub fn walk_ast<F: Fold + ?Sized>(folder: &mut F, ast: AST) -> AST {
+    match ast {
+        AST::Document(node) => folder.fold_document(node),
+        AST::Stmt(node) => folder.fold_stmt(node),
+        AST::Expr(node) => folder.fold_expr(node),
+        AST::Angle(node) => folder.fold_angle(node),
+        AST::Square(node) => folder.fold_square(node),
+        AST::Curly(node) => folder.fold_curly(node),
+        AST::Block(node) => folder.fold_block(node),
+        AST::Named(node) => folder.fold_named(node),
+        AST::Ident(leaf) => folder.fold_ident(leaf),
+        AST::Module(leaf) => folder.fold_module(leaf),
+        AST::Text(leaf) => folder.fold_text(leaf),
+        AST::Raw(raw) => folder.fold_raw(raw),
+        AST::Invalid(leaf) => folder.fold_invalid(leaf),
+        AST::Ignored(leaf) => folder.fold_ignored(leaf),
+    }
+}
+
+pub fn walk_inner<F: Fold + ?Sized>(folder: &mut F, node: InnerNode) -> InnerNode {
+    InnerNode {
+        children: node
+            .children
+            .into_iter()
+            .map(|child| folder.fold_ast(child))
+            .collect(),
+        ..node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brack_tokenizer::tokens::mock_location;
+
+    use crate::ast::{
+        assert_ast_eq, new_angle, new_expr, new_ident, new_module, new_stmt, new_text, InnerNode,
+        AST,
+    };
+
+    use super::Fold;
+
+    /// Replaces every macro call with the text of its identifier.
+    struct ReplaceAngles;
+
+    impl Fold for ReplaceAngles {
+        fn fold_angle(&mut self, node: InnerNode) -> AST {
+            new_text(node.children[1].value(), node.location)
+        }
+    }
+
+    #[test]
+    fn test_fold_replaces_nodes_with_other_kinds() {
+        let angle = new_angle(
+            vec![
+                new_module(Some("std".to_string()), mock_location()),
+                new_ident(Some("toc".to_string()), mock_location()),
+            ],
+            mock_location(),
+        );
+        let ast = new_stmt(
+            vec![new_expr(vec![angle], mock_location())],
+            mock_location(),
+        );
+        let folded = ReplaceAngles.fold_ast(ast);
+        let expected = new_stmt(
+            vec![new_expr(
+                vec![new_text(Some("toc".to_string()), mock_location())],
+                mock_location(),
+            )],
+            mock_location(),
+        );
+        assert_ast_eq(&folded, &expected);
+    }
+}
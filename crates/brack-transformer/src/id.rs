@@ -0,0 +1,82 @@
+use brack_parser::id::path_hash;
+
+use crate::ast::AST;
+
+pub use brack_parser::id::IdStrategy;
+
+/// Reassigns the ids of all nodes according to `strategy`.
+/// A node's id is the hash of `path` followed by the child indices from the root, e.g. `0d4f...-0-2-1`.
+pub fn assign_ids(ast: &mut AST, path: &str, strategy: IdStrategy) {
+    match strategy {
+        IdStrategy::Deterministic => aux(ast, path_hash(path)),
+        // Nodes already have random ids when they are created.
+        IdStrategy::Random => (),
+    }
+}
+
+/// Reassigns the ids of a subtree with `id` as the id of its root, e.g. `id-0-1`.
+/// This is used for nodes created in place of the node `id`, such as the result of a macro.
+pub fn assign_ids_from(ast: &mut AST, id: &str) {
+    aux(ast, id.to_string());
+}
+
+fn aux(ast: &mut AST, id: String) {
+    match ast {
+        AST::Document(node)
+        | AST::Stmt(node)
+        | AST::Expr(node)
+        | AST::Angle(node)
+        | AST::Square(node)
+        | AST::Curly(node)
+        | AST::Block(node)
+        | AST::Named(node) => {
+            for (i, child) in node.children.iter_mut().enumerate() {
+                aux(child, format!("{}-{}", id, i));
+            }
+            node.id = id;
+        }
+        AST::Ident(leaf)
+        | AST::Module(leaf)
+        | AST::Text(leaf)
+        | AST::Invalid(leaf)
+        | AST::Ignored(leaf) => leaf.id = id,
+        AST::Raw(raw) => raw.id = id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_parser::parse::parse;
+    use brack_tokenizer::tokenize::tokenize_str;
+
+    use crate::{test_util::transform_str, transform::transform};
+
+    use super::{assign_ids, IdStrategy};
+
+    #[test]
+    fn test_deterministic_ids_are_stable() -> Result<()> {
+        let text = "Hello, [std.* World]!\n\n{std.# Title}";
+        let ast1 = transform_str(text)?;
+        let ast2 = transform_str(text)?;
+        assert_eq!(ast1, ast2);
+        let mut ast3 = transform_str(text)?;
+        assign_ids(&mut ast3, "docs/other.[]", IdStrategy::Deterministic);
+        assert_ne!(ast1.id(), ast3.id());
+        let square = &ast1.try_children()?[0].try_children()?[0].try_children()?[1];
+        assert_eq!(square.id(), format!("{}-0-0-1", ast1.id()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_ids_differ() -> Result<()> {
+        let tokens = tokenize_str("Hello, [std.* World]!")?;
+        let (cst, _) = parse(&tokens);
+        let (mut ast1, _) = transform(&cst);
+        let (mut ast2, _) = transform(&cst);
+        assign_ids(&mut ast1, "docs/index.[]", IdStrategy::Random);
+        assign_ids(&mut ast2, "docs/index.[]", IdStrategy::Random);
+        assert_ne!(ast1.id(), ast2.id());
+        Ok(())
+    }
+}
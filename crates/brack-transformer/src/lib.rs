@@ -1,12 +1,20 @@
 mod angle;
 pub mod ast;
 mod backslash;
+pub mod command;
 mod curly;
 mod document;
 mod error;
 mod expr;
+pub mod fold;
+pub mod id;
 mod simplify;
 mod square;
 mod stmt;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod transform;
+pub mod unparse;
 mod utils;
+pub mod visit;
+pub mod visit_mut;
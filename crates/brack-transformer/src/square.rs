@@ -1,30 +1,32 @@
 use brack_parser::cst::{InnerNode, CST};
-use brack_tokenizer::tokens::merge_location;
+use brack_tokenizer::tokens::{merge_location, Location};
 
 use crate::{
     error::TransformError,
     simplify,
     utils::{
         check_if_dot, check_if_ident_or_angle_bracket, check_if_module_or_angle_bracket,
-        check_unexpected_dot, check_valid_arguments, remove_elements_not_included_ast,
-        remove_whitespaces_and_newlines,
+        check_unexpected_dot, check_valid_arguments, collapse_whitespaces_in_arguments,
+        remove_elements_not_included_ast, resolve_equals_in_arguments,
     },
 };
 
-fn check_if_the_first_and_last_node_are_brackets(csts: &[CST]) -> Vec<TransformError> {
+fn check_if_the_first_and_last_node_are_brackets(
+    csts: &[CST],
+    location: &Location,
+) -> Vec<TransformError> {
     let mut errors = vec![];
-    match (csts[0].clone(), csts[csts.len() - 1].clone()) {
-        (CST::SquareBracketOpen(_), CST::SquareBracketClose(_)) => (),
-        (CST::SquareBracketOpen(left), CST::AngleBracketClose(right))
-        | (CST::SquareBracketOpen(left), CST::CurlyBracketClose(right)) => errors.push(
+    match (csts.first(), csts.last()) {
+        (Some(CST::SquareBracketOpen(_)), Some(CST::SquareBracketClose(_))) => (),
+        (Some(CST::SquareBracketOpen(left)), Some(CST::AngleBracketClose(right)))
+        | (Some(CST::SquareBracketOpen(left)), Some(CST::CurlyBracketClose(right))) => errors.push(
             TransformError::MismatchedBracket(merge_location(&left.location, &right.location)),
         ),
-        (CST::SquareBracketOpen(left), right) => errors.push(TransformError::SquareNotClosed(
-            merge_location(&left.location, &right.location()),
-        )),
-        _ => panic!(
-            "Maybe cst parser is broken because CST::Square must have bracket-open node first."
+        (Some(CST::SquareBracketOpen(left)), Some(right)) => errors.push(
+            TransformError::SquareNotClosed(merge_location(&left.location, &right.location())),
         ),
+        // CST::Square must have a bracket-open node first.
+        _ => errors.push(TransformError::MalformedTree(location.clone())),
     }
     errors
 }
@@ -32,7 +34,12 @@ fn check_if_the_first_and_last_node_are_brackets(csts: &[CST]) -> Vec<TransformE
 pub fn simplify(cst: &CST) -> (CST, Vec<TransformError>) {
     let node = match cst {
         CST::Square(node) => node,
-        _ => panic!("Cannot pass non-square-bracket node to curly::simplify"),
+        _ => {
+            return (
+                cst.clone(),
+                vec![TransformError::MalformedTree(cst.location())],
+            )
+        }
     };
     let mut errors = vec![];
     let mut csts = vec![];
@@ -46,14 +53,18 @@ pub fn simplify(cst: &CST) -> (CST, Vec<TransformError>) {
         errors.append(&mut node_errors);
     }
 
-    errors.append(&mut check_if_the_first_and_last_node_are_brackets(&csts));
+    errors.append(&mut check_if_the_first_and_last_node_are_brackets(
+        &csts,
+        &node.location,
+    ));
     errors.append(&mut check_if_module_or_angle_bracket(&csts));
     errors.append(&mut check_if_dot(&csts));
     errors.append(&mut check_if_ident_or_angle_bracket(&csts));
     errors.append(&mut check_unexpected_dot(&csts));
-    let csts = remove_whitespaces_and_newlines(&csts);
+    let csts = collapse_whitespaces_in_arguments(&csts);
     let (csts, mut new_errors) = check_valid_arguments(&csts);
     errors.append(&mut new_errors);
+    let csts = resolve_equals_in_arguments(&csts);
     let csts = remove_elements_not_included_ast(&csts);
 
     (
@@ -65,3 +76,24 @@ pub fn simplify(cst: &CST) -> (CST, Vec<TransformError>) {
         errors,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use brack_parser::cst::{new_document, new_expr, new_square, new_stmt};
+
+    use crate::{error::TransformError, transform::transform};
+
+    #[test]
+    fn test_malformed_square_is_an_error() -> Result<()> {
+        let mut expr = new_expr();
+        expr.add(new_square())?;
+        let mut stmt = new_stmt();
+        stmt.add(expr)?;
+        let mut document = new_document();
+        document.add(stmt)?;
+        let (_, errors) = transform(&document);
+        assert!(matches!(errors[..], [TransformError::MalformedTree(_)]));
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use brack_parser::parse::parse;
+use brack_tokenizer::tokenize::tokenize_str;
+
+use crate::{
+    ast::AST,
+    id::{assign_ids, IdStrategy},
+    transform::transform,
+};
+
+/// Transforms `text` as the document `test.[]` with deterministic ids.
+/// The text must have no parse or transform errors.
+/// Other crates use this in their tests through the `test-util` feature.
+pub fn transform_str(text: &str) -> Result<AST> {
+    let tokens = tokenize_str(text)?;
+    let (cst, errors) = parse(&tokens);
+    assert!(errors.is_empty(), "{:?}", errors);
+    let (mut ast, errors) = transform(&cst);
+    assert!(errors.is_empty(), "{:?}", errors);
+    assign_ids(&mut ast, "test.[]", IdStrategy::Deterministic);
+    Ok(ast)
+}
@@ -1,58 +1,86 @@
 use brack_parser::cst::CST;
+use brack_tokenizer::tokens::merge_location;
 
 use crate::{
     ast::{
-        new_angle, new_curly, new_document, new_expr, new_ident, new_invalid, new_module,
-        new_square, new_stmt, new_text, AST,
+        new_angle, new_block, new_curly, new_document, new_expr, new_ident, new_invalid,
+        new_module, new_named, new_raw, new_square, new_stmt, new_text, AST,
     },
     error::TransformError,
     simplify,
 };
 
 pub fn transform(cst: &CST) -> (AST, Vec<TransformError>) {
-    let (cst, errors) = simplify::simplify(cst);
+    let (cst, mut errors) = simplify::simplify(cst);
 
-    fn aux(cst: &CST) -> AST {
+    fn aux(cst: &CST, errors: &mut Vec<TransformError>) -> AST {
         match cst {
             CST::Document(node) => {
                 let mut children = vec![];
                 for child in node.children.clone() {
-                    children.push(aux(&child));
+                    children.push(aux(&child, errors));
                 }
                 new_document(children, node.location.clone())
             }
             CST::Stmt(node) => {
                 let mut children = vec![];
                 for child in node.children.clone() {
-                    children.push(aux(&child));
+                    children.push(aux(&child, errors));
                 }
                 new_stmt(children, node.location.clone())
             }
             CST::Expr(node) => {
+                if let [CST::Text(name), CST::Equal(_), value @ ..] = node.children.as_slice() {
+                    let mut children = vec![];
+                    for child in value {
+                        children.push(aux(child, errors));
+                    }
+                    let value_location = match (value.first(), value.last()) {
+                        (Some(first), Some(last)) => {
+                            merge_location(&first.location(), &last.location())
+                        }
+                        _ => name.location.clone(),
+                    };
+                    let location = merge_location(&name.location, &value_location);
+                    return new_named(
+                        new_ident(name.value.clone(), name.location.clone()),
+                        new_expr(children, value_location),
+                        location,
+                    );
+                }
                 let mut children = vec![];
                 for child in node.children.clone() {
-                    children.push(aux(&child));
+                    children.push(aux(&child, errors));
                 }
                 new_expr(children, node.location.clone())
             }
             CST::Angle(node) => {
                 let mut children = vec![];
                 for child in node.children.clone() {
-                    children.push(aux(&child));
+                    children.push(aux(&child, errors));
                 }
                 new_angle(children, node.location.clone())
             }
             CST::Curly(node) => {
                 let mut children = vec![];
+                let mut stmts = vec![];
                 for child in node.children.clone() {
-                    children.push(aux(&child));
+                    match child {
+                        CST::Stmt(_) => stmts.push(aux(&child, errors)),
+                        child => children.push(aux(&child, errors)),
+                    }
+                }
+                // The statements of the body are passed to the command as one argument.
+                if let (Some(first), Some(last)) = (stmts.first(), stmts.last()) {
+                    let location = merge_location(&first.location(), &last.location());
+                    children.push(new_block(stmts, location));
                 }
                 new_curly(children, node.location.clone())
             }
             CST::Square(node) => {
                 let mut children = vec![];
                 for child in node.children.clone() {
-                    children.push(aux(&child));
+                    children.push(aux(&child, errors));
                 }
                 new_square(children, node.location.clone())
             }
@@ -60,11 +88,17 @@ pub fn transform(cst: &CST) -> (AST, Vec<TransformError>) {
             CST::Module(node) => new_module(node.value.clone(), node.location.clone()),
             CST::Invalid(node) => new_invalid(node.location.clone()),
             CST::Text(node) => new_text(node.value.clone(), node.location.clone()),
-            node => panic!("Cannot pass non-ast node to transform::aux: {:?}", node),
+            CST::Raw(node) => new_raw(node.raw.clone(), node.location.clone()),
+            // Nodes which are not included in the AST must have been removed by `simplify`.
+            node => {
+                errors.push(TransformError::MalformedTree(node.location()));
+                new_invalid(node.location())
+            }
         }
     }
 
-    (aux(&cst), errors)
+    let ast = aux(&cst, &mut errors);
+    (ast, errors)
 }
 
 // #[cfg(test)]
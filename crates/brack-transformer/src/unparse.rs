@@ -0,0 +1,144 @@
+use crate::ast::{InnerNode, AST};
+
+/// Turns an AST back into Brack source text.
+/// Unlike `brack_parser::print::print`, the layout of the original source is not kept,
+/// but the output is parsed and transformed into the same AST.
+pub fn unparse(ast: &AST) -> String {
+    let mut source = String::new();
+    unparse_to(ast, false, &mut source);
+    source
+}
+
+fn unparse_to(ast: &AST, nested: bool, source: &mut String) {
+    match ast {
+        AST::Document(node) => join(node, "\n\n", nested, source),
+        AST::Stmt(node) => join(node, "\n", nested, source),
+        AST::Expr(node) => {
+            for child in &node.children {
+                unparse_to(child, nested, source);
+            }
+        }
+        AST::Angle(node) => unparse_command(node, '<', '>', source),
+        AST::Square(node) => unparse_command(node, '[', ']', source),
+        AST::Curly(node) => unparse_command(node, '{', '}', source),
+        AST::Block(node) => join(node, "\n\n", false, source),
+        AST::Named(node) => {
+            if let [name, value] = &node.children[..] {
+                unparse_to(name, true, source);
+                source.push('=');
+                unparse_to(value, true, source);
+            }
+        }
+        AST::Ident(leaf) | AST::Module(leaf) => {
+            source.push_str(leaf.value.as_deref().unwrap_or_default())
+        }
+        AST::Text(leaf) => escape(leaf.value.as_deref().unwrap_or_default(), nested, source),
+        AST::Raw(raw) => source.push_str(&raw.raw.source()),
+        AST::Invalid(_) | AST::Ignored(_) => (),
+    }
+}
+
+fn join(node: &InnerNode, separator: &str, nested: bool, source: &mut String) {
+    for (i, child) in node.children.iter().enumerate() {
+        if i > 0 {
+            source.push_str(separator);
+        }
+        unparse_to(child, nested, source);
+    }
+}
+
+/// Children of a command are the module, the identifier and the arguments.
+/// The body of a block command starts on the line following the arguments.
+fn unparse_command(node: &InnerNode, open: char, close: char, source: &mut String) {
+    source.push(open);
+    let mut children = node.children.iter();
+    if let Some(module) = children.next() {
+        unparse_to(module, true, source);
+        source.push('.');
+    }
+    if let Some(ident) = children.next() {
+        unparse_to(ident, true, source);
+    }
+    for (i, argument) in children.enumerate() {
+        if let AST::Block(_) = argument {
+            source.push('\n');
+            unparse_to(argument, false, source);
+            source.push('\n');
+            continue;
+        }
+        source.push_str(if i == 0 { " " } else { ", " });
+        unparse_to(argument, true, source);
+    }
+    source.push(close);
+}
+
+/// Escapes brackets and backslashes, and also dots, commas and equals signs inside commands.
+/// `//` at the beginning of a line is escaped so that it is not read as a comment.
+fn escape(text: &str, nested: bool, source: &mut String) {
+    let mut line_start = source.is_empty() || source.ends_with('\n');
+    for c in text.chars() {
+        match c {
+            '\\' | '<' | '>' | '{' | '}' | '[' | ']' => source.push('\\'),
+            '.' | ',' | '=' if nested => source.push('\\'),
+            '/' if line_start && !nested => source.push('\\'),
+            _ => (),
+        }
+        source.push(c);
+        line_start = c == '\n';
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::{ast::assert_ast_eq, test_util::transform_str};
+
+    use super::unparse;
+
+    fn assert_round_trip(text: &str, expected: &str) -> Result<()> {
+        let ast = transform_str(text)?;
+        let unparsed = unparse(&ast);
+        assert_eq!(unparsed, expected);
+        assert_ast_eq(&transform_str(&unparsed)?, &ast);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unparse_commands() -> Result<()> {
+        assert_round_trip(
+            "Hello, [std.* World]!\n<std.toc>\n\n{std.# Title}\n\n[std.@ Brack, url=https://example\\.com]",
+            "Hello, [std.* World]!\n<std.toc>\n\n{std.# Title}\n\n[std.@ Brack, url=https://example\\.com]",
+        )
+    }
+
+    #[test]
+    fn test_unparse_spaces_in_arguments() -> Result<()> {
+        assert_round_trip(
+            "[std.*  Hello   World ,\n  again ]\n\n<std.x a = b>",
+            "[std.* Hello World, again]\n\n<std.x a=b>",
+        )
+    }
+
+    #[test]
+    fn test_unparse_escapes() -> Result<()> {
+        assert_round_trip(
+            "\\<a\\> \\[b\\] \\{c\\} \\\\ 1. 2, a=b\n\\// not a comment\n[std.* x\\, y\\.z\\=\\[w\\]]",
+            "\\<a\\> \\[b\\] \\{c\\} \\\\ 1. 2, a=b\n\\// not a comment\n[std.* x\\, y\\.z\\=\\[w\\]]",
+        )?;
+        assert_round_trip("C:\\\\[std.* dir]", "C:\\\\[std.* dir]")
+    }
+
+    #[test]
+    fn test_unparse_block_commands() -> Result<()> {
+        assert_round_trip(
+            "{std.callout note, title=Tips\nFirst, [std.* second].\n\n{std.code\n```rust\nfn main() {}\n```\n}\n}",
+            "{std.callout note, title=Tips\nFirst, [std.* second].\n\n{std.code\n```rust\nfn main() {}\n```\n}\n}",
+        )
+    }
+
+    #[test]
+    fn test_unparse_raw_blocks() -> Result<()> {
+        assert_round_trip("```rust\nfn main() {}\n```", "```rust\nfn main() {}\n```")
+    }
+}
@@ -1,4 +1,5 @@
-use brack_parser::cst::{new_expr, CST};
+use brack_parser::cst::{new_text, InnerNode, CST};
+use brack_tokenizer::tokens::mock_location;
 
 use crate::error::TransformError;
 
@@ -43,6 +44,8 @@ pub fn remove_elements_not_included_ast(csts: &[CST]) -> Vec<CST> {
         match cst {
             CST::Whitespace(_)
             | CST::Newline(_)
+            | CST::Comment(_)
+            | CST::FrontMatter(_)
             | CST::Comma(_)
             | CST::Dot(_)
             | CST::AngleBracketOpen(_)
@@ -64,26 +67,26 @@ pub fn check_valid_arguments(csts: &[CST]) -> (Vec<CST>, Vec<TransformError>) {
     }
     let mut errors = vec![];
     let mut new_csts = csts[0..4].to_vec(); // [AngleBracketOpen, Module, Dot, Ident
-    let mut expr = new_expr();
+    let mut expr = InnerNode::new(mock_location());
     let mut previous_comma = false;
     for i in 4..csts.len() {
         match csts[i].clone() {
             CST::Comma(_) => {
-                if expr.children().is_empty() {
+                if expr.children.is_empty() {
                     errors.push(TransformError::UnexpectedComma(csts[i].location()));
                     continue;
                 }
-                new_csts.push(expr);
-                expr = new_expr();
+                let node = std::mem::replace(&mut expr, InnerNode::new(mock_location()));
+                new_csts.push(CST::Expr(node));
                 previous_comma = true;
             }
             CST::AngleBracketClose(_) | CST::CurlyBracketClose(_) | CST::SquareBracketClose(_) => {
-                if !expr.children().is_empty() {
-                    new_csts.push(expr.clone());
+                if !expr.children.is_empty() {
+                    let node = std::mem::replace(&mut expr, InnerNode::new(mock_location()));
+                    new_csts.push(CST::Expr(node));
                 } else if previous_comma {
                     errors.push(TransformError::UnexpectedComma(csts[i - 1].location()));
                 }
-                expr = new_expr();
                 new_csts.push(csts[i].clone());
                 break;
             }
@@ -97,12 +100,53 @@ pub fn check_valid_arguments(csts: &[CST]) -> (Vec<CST>, Vec<TransformError>) {
             }
         }
     }
-    if !expr.children().is_empty() {
-        new_csts.push(expr);
+    if !expr.children.is_empty() {
+        new_csts.push(CST::Expr(expr));
     }
     (new_csts, errors)
 }
 
+pub fn is_argument_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Keeps `=` only where it separates the name of a named argument from its value.
+/// Any other `=` is a part of the text.
+pub fn resolve_equals_in_arguments(csts: &[CST]) -> Vec<CST> {
+    let mut new_csts = vec![];
+    for cst in csts {
+        let node = match cst {
+            CST::Expr(node) => node,
+            _ => {
+                new_csts.push(cst.clone());
+                continue;
+            }
+        };
+        let named = matches!(
+            node.children.as_slice(),
+            [CST::Text(name), CST::Equal(_), ..]
+                if is_argument_name(name.value.as_deref().unwrap_or_default())
+        );
+        let mut expr = InnerNode::new(mock_location());
+        for (i, child) in node.children.iter().enumerate() {
+            match child {
+                CST::Equal(leaf) if !(named && i == 1) => {
+                    expr.add(new_text("=".to_string(), leaf.location.clone()))
+                }
+                child => expr.add(child.clone()),
+            }
+        }
+        expr.location = node.location.clone();
+        new_csts.push(CST::Expr(expr));
+    }
+    new_csts
+}
+
 pub fn check_unexpected_dot(csts: &[CST]) -> Vec<TransformError> {
     let mut errors = vec![];
     for cst in csts.iter().skip(3) {
@@ -113,12 +157,42 @@ pub fn check_unexpected_dot(csts: &[CST]) -> Vec<TransformError> {
     errors
 }
 
-pub fn remove_whitespaces_and_newlines(csts: &Vec<CST>) -> Vec<CST> {
-    let mut result = vec![];
+/// Replaces whitespaces and newlines between two parts of an argument with a space,
+/// and removes the others, e.g. those around commas, brackets and equals signs.
+pub fn collapse_whitespaces_in_arguments(csts: &[CST]) -> Vec<CST> {
+    let is_part = |cst: &CST| {
+        !matches!(
+            cst,
+            CST::Whitespace(_)
+                | CST::Newline(_)
+                | CST::Comma(_)
+                | CST::Equal(_)
+                | CST::Dot(_)
+                | CST::Module(_)
+                | CST::Ident(_)
+                | CST::AngleBracketOpen(_)
+                | CST::AngleBracketClose(_)
+                | CST::CurlyBracketOpen(_)
+                | CST::CurlyBracketClose(_)
+                | CST::SquareBracketOpen(_)
+                | CST::SquareBracketClose(_)
+        )
+    };
+    let mut result: Vec<CST> = vec![];
+    let mut space = None;
     for cst in csts {
         match cst {
-            CST::Whitespace(_) | CST::Newline(_) => (),
-            _ => result.push(cst.clone()),
+            CST::Whitespace(_) | CST::Newline(_) => {
+                space.get_or_insert(cst.location());
+            }
+            _ => {
+                if let Some(location) = space.take() {
+                    if result.last().is_some_and(is_part) && is_part(cst) {
+                        result.push(new_text(" ".to_string(), location));
+                    }
+                }
+                result.push(cst.clone());
+            }
         }
     }
     result
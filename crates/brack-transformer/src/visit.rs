@@ -0,0 +1,132 @@
+use crate::ast::{InnerNode, LeafNode, RawNode, AST};
+
+/// Traverses an AST by reference.
+/// Each method walks into the children by default,
+/// so an implementation only overrides the nodes it is interested in.
+pub trait Visit {
+    fn visit_ast(&mut self, ast: &AST) {
+        walk_ast(self, ast);
+    }
+
+    fn visit_document(&mut self, node: &InnerNode) {
+        walk_inner(self, node);
+    }
+
+    fn visit_stmt(&mut self, node: &InnerNode) {
+        walk_inner(self, node);
+    }
+
+    fn visit_expr(&mut self, node: &InnerNode) {
+        walk_inner(self, node);
+    }
+
+    fn visit_angle(&mut self, node: &InnerNode) {
+        walk_inner(self, node);
+    }
+
+    fn visit_square(&mut self, node: &InnerNode) {
+        walk_inner(self, node);
+    }
+
+    fn visit_curly(&mut self, node: &InnerNode) {
+        walk_inner(self, node);
+    }
+
+    fn visit_block(&mut self, node: &InnerNode) {
+        walk_inner(self, node);
+    }
+
+    fn visit_named(&mut self, node: &InnerNode) {
+        walk_inner(self, node);
+    }
+
+    fn visit_ident(&mut self, _leaf: &LeafNode) {}
+
+    fn visit_module(&mut self, _leaf: &LeafNode) {}
+
+    fn visit_text(&mut self, _leaf: &LeafNode) {}
+
+    fn visit_raw(&mut self, _raw: &RawNode) {}
+
+    fn visit_invalid(&mut self, _leaf: &LeafNode) {}
+
+    fn visit_ignored(&mut self, _leaf: &LeafNode) {}
+}
+
+pub fn walk_ast<V: Visit + ?Sized>(visitor: &mut V, ast: &AST) {
+    match ast {
+        AST::Document(node) => visitor.visit_document(node),
+        AST::Stmt(node) => visitor.visit_stmt(node),
+        AST::Expr(node) => visitor.visit_expr(node),
+        AST::Angle(node) => visitor.visit_angle(node),
+        AST::Square(node) => visitor.visit_square(node),
+        AST::Curly(node) => visitor.visit_curly(node),
+        AST::Block(node) => visitor.visit_block(node),
+        AST::Named(node) => visitor.visit_named(node),
+        AST::Ident(leaf) => visitor.visit_ident(leaf),
+        AST::Module(leaf) => visitor.visit_module(leaf),
+        AST::Text(leaf) => visitor.visit_text(leaf),
+        AST::Raw(raw) => visitor.visit_raw(raw),
+        AST::Invalid(leaf) => visitor.visit_invalid(leaf),
+        AST::Ignored(leaf) => visitor.visit_ignored(leaf),
+    }
+}
+
+pub fn walk_inner<V: Visit + ?Sized>(visitor: &mut V, node: &InnerNode) {
+    for child in &node.children {
+        visitor.visit_ast(child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brack_tokenizer::tokens::mock_location;
+
+    use crate::ast::{
+        new_document, new_expr, new_ident, new_module, new_square, new_stmt, new_text, LeafNode,
+    };
+
+    use super::Visit;
+
+    #[derive(Default)]
+    struct TextCollector {
+        texts: Vec<String>,
+    }
+
+    impl Visit for TextCollector {
+        fn visit_text(&mut self, leaf: &LeafNode) {
+            self.texts.push(leaf.value.clone().unwrap_or_default());
+        }
+    }
+
+    #[test]
+    fn test_visit_reaches_nested_texts() {
+        let square = new_square(
+            vec![
+                new_module(Some("std".to_string()), mock_location()),
+                new_ident(Some("*".to_string()), mock_location()),
+                new_expr(
+                    vec![new_text(Some("World".to_string()), mock_location())],
+                    mock_location(),
+                ),
+            ],
+            mock_location(),
+        );
+        let ast = new_document(
+            vec![new_stmt(
+                vec![new_expr(
+                    vec![
+                        new_text(Some("Hello, ".to_string()), mock_location()),
+                        square,
+                    ],
+                    mock_location(),
+                )],
+                mock_location(),
+            )],
+            mock_location(),
+        );
+        let mut collector = TextCollector::default();
+        collector.visit_ast(&ast);
+        assert_eq!(collector.texts, vec!["Hello, ", "World"]);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::ast::{InnerNode, LeafNode, RawNode, AST};
+
+/// Traverses an AST by mutable reference and modifies nodes in place.
+/// Each method walks into the children by default.
+pub trait VisitMut {
+    fn visit_ast_mut(&mut self, ast: &mut AST) {
+        walk_ast_mut(self, ast);
+    }
+
+    fn visit_document_mut(&mut self, node: &mut InnerNode) {
+        walk_inner_mut(self, node);
+    }
+
+    fn visit_stmt_mut(&mut self, node: &mut InnerNode) {
+        walk_inner_mut(self, node);
+    }
+
+    fn visit_expr_mut(&mut self, node: &mut InnerNode) {
+        walk_inner_mut(self, node);
+    }
+
+    fn visit_angle_mut(&mut self, node: &mut InnerNode) {
+        walk_inner_mut(self, node);
+    }
+
+    fn visit_square_mut(&mut self, node: &mut InnerNode) {
+        walk_inner_mut(self, node);
+    }
+
+    fn visit_curly_mut(&mut self, node: &mut InnerNode) {
+        walk_inner_mut(self, node);
+    }
+
+    fn visit_block_mut(&mut self, node: &mut InnerNode) {
+        walk_inner_mut(self, node);
+    }
+
+    fn visit_named_mut(&mut self, node: &mut InnerNode) {
+        walk_inner_mut(self, node);
+    }
+
+    fn visit_ident_mut(&mut self, _leaf: &mut LeafNode) {}
+
+    fn visit_module_mut(&mut self, _leaf: &mut LeafNode) {}
+
+    fn visit_text_mut(&mut self, _leaf: &mut LeafNode) {}
+
+    fn visit_raw_mut(&mut self, _raw: &mut RawNode) {}
+
+    fn visit_invalid_mut(&mut self, _leaf: &mut LeafNode) {}
+
+    fn visit_ignored_mut(&mut self, _leaf: &mut LeafNode) {}
+}
+
+pub fn walk_ast_mut<V: VisitMut + ?Sized>(visitor: &mut V, ast: &mut AST) {
+    match ast {
+        AST::Document(node) => visitor.visit_document_mut(node),
+        AST::Stmt(node) => visitor.visit_stmt_mut(node),
+        AST::Expr(node) => visitor.visit_expr_mut(node),
+        AST::Angle(node) => visitor.visit_angle_mut(node),
+        AST::Square(node) => visitor.visit_square_mut(node),
+        AST::Curly(node) => visitor.visit_curly_mut(node),
+        AST::Block(node) => visitor.visit_block_mut(node),
+        AST::Named(node) => visitor.visit_named_mut(node),
+        AST::Ident(leaf) => visitor.visit_ident_mut(leaf),
+        AST::Module(leaf) => visitor.visit_module_mut(leaf),
+        AST::Text(leaf) => visitor.visit_text_mut(leaf),
+        AST::Raw(raw) => visitor.visit_raw_mut(raw),
+        AST::Invalid(leaf) => visitor.visit_invalid_mut(leaf),
+        AST::Ignored(leaf) => visitor.visit_ignored_mut(leaf),
+    }
+}
+
+pub fn walk_inner_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut InnerNode) {
+    for child in node.children.iter_mut() {
+        visitor.visit_ast_mut(child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use brack_tokenizer::tokens::mock_location;
+
+    use crate::ast::{assert_ast_eq, new_expr, new_stmt, new_text, LeafNode};
+
+    use super::VisitMut;
+
+    struct Uppercase;
+
+    impl VisitMut for Uppercase {
+        fn visit_text_mut(&mut self, leaf: &mut LeafNode) {
+            leaf.value = leaf.value.as_ref().map(|value| value.to_uppercase());
+        }
+    }
+
+    #[test]
+    fn test_visit_mut_modifies_texts_in_place() {
+        let mut ast = new_stmt(
+            vec![new_expr(
+                vec![new_text(Some("brack".to_string()), mock_location())],
+                mock_location(),
+            )],
+            mock_location(),
+        );
+        Uppercase.visit_ast_mut(&mut ast);
+        let expected = new_stmt(
+            vec![new_expr(
+                vec![new_text(Some("BRACK".to_string()), mock_location())],
+                mock_location(),
+            )],
+            mock_location(),
+        );
+        assert_ast_eq(&ast, &expected);
+    }
+}
@@ -1,8 +1,19 @@
 use std::collections::HashMap;
 
 use anyhow::Result;
-use brack::sub_commands::SubCommands;
+use brack::sub_commands::{SubCommands, TraceFormat};
+use brack_expander::include::resolve_includes;
+use brack_parser::{cst::CST, id::id_path};
 use brack_plugin::{feature_flag::FeatureFlag, plugin::Plugin, plugins::Plugins};
+use brack_project_manager::metadata::document_metadata;
+use brack_tokenizer::{
+    diagnostic::{render_error, stderr_supports_color, Diagnostic},
+    front_matter::front_matter,
+    source_map::{FileId, SourceMap},
+    tokens::Token,
+};
+use brack_transformer::ast::AST;
+use brack_transformer::id::{assign_ids, IdStrategy};
 use clap::Parser;
 use regex::Regex;
 
@@ -12,17 +23,118 @@ struct Args {
     subcommand: SubCommands,
 }
 
+/// Fails with all the diagnostics rendered if there are any.
+fn report(diagnostics: Vec<Diagnostic>, source_map: &SourceMap) -> Result<()> {
+    if diagnostics.is_empty() {
+        return Ok(());
+    }
+    let color = stderr_supports_color();
+    let messages = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.render_with_color(source_map, color))
+        .collect::<Vec<_>>();
+    anyhow::bail!(
+        "{}\n\n{} error(s) found.",
+        messages.join("\n\n"),
+        diagnostics.len()
+    );
+}
+
+fn parse(tokens: &[Token], source_map: &SourceMap) -> Result<CST> {
+    let (cst, errors) = brack_parser::parse::parse(tokens);
+    report(
+        errors.iter().map(|error| error.to_diagnostic()).collect(),
+        source_map,
+    )?;
+    Ok(cst)
+}
+
+/// Parses and transforms a file and resolves its includes, reporting the errors of all phases together.
+fn transform(file: FileId, source_map: &mut SourceMap) -> Result<AST> {
+    let tokens = brack_tokenizer::tokenize::tokenize_file(source_map, file)?;
+    let (cst, parse_errors) = brack_parser::parse::parse(&tokens);
+    let (ast, errors) = brack_transformer::transform::transform(&cst);
+    let (ast, include_errors) = resolve_includes(ast, file, source_map);
+    let diagnostics = parse_errors
+        .iter()
+        .map(|error| error.to_diagnostic())
+        .chain(errors.iter().map(|error| error.to_diagnostic()))
+        .chain(include_errors)
+        .collect();
+    report(diagnostics, source_map)?;
+    Ok(ast)
+}
+
+fn render(error: anyhow::Error, source_map: &SourceMap) -> anyhow::Error {
+    anyhow::anyhow!(render_error(&error, source_map, stderr_supports_color()))
+}
+
+/// Passes the front matter of a file to plugins.
+/// Outside of a project, there is no `[document]` of `Brack.toml` to merge with.
+fn set_metadata(file: FileId, source_map: &SourceMap, plugins: &mut Plugins) -> Result<()> {
+    let front_matter = source_map
+        .get(file)
+        .and_then(|source| front_matter(&source.text, file));
+    let metadata = document_metadata(None, front_matter.as_ref())
+        .map_err(|error| render(error, source_map))?;
+    plugins.set_metadata(metadata);
+    Ok(())
+}
+
+/// Expands macros, printing the trace to stderr when asked so that the output stays intact.
+fn expand(
+    ast: &AST,
+    plugins: &mut Plugins,
+    macro_depth: usize,
+    trace: Option<TraceFormat>,
+    source_map: &SourceMap,
+) -> Result<AST> {
+    let result = match trace {
+        None => brack_expander::expand::expand(ast, plugins, macro_depth),
+        Some(format) => {
+            let (result, trace) = brack_expander::expand::expand_traced(ast, plugins, macro_depth);
+            match format {
+                TraceFormat::Text => eprint!("{}", trace.render(source_map)),
+                TraceFormat::Json => eprintln!("{}", serde_json::to_string(&trace)?),
+            }
+            result
+        }
+    };
+    result.map_err(|error| render(error, source_map))
+}
+
 pub fn run_compile(subcommand: SubCommands) -> Result<()> {
     let mut pathes = HashMap::new();
 
-    let (plugins_dir_path, _, filename, output_level, json) = match subcommand {
+    let (
+        plugins_dir_path,
+        _,
+        filename,
+        output_level,
+        json,
+        random_ids,
+        macro_depth,
+        trace_expansion,
+    ) = match subcommand {
         SubCommands::Compile {
             plugins_dir_path,
             backend,
             filename,
             output_level,
             json,
-        } => (plugins_dir_path, backend, filename, output_level, json),
+            random_ids,
+            macro_depth,
+            trace_expansion,
+        } => (
+            plugins_dir_path,
+            backend,
+            filename,
+            output_level,
+            json,
+            random_ids,
+            macro_depth,
+            trace_expansion,
+        ),
         _ => unreachable!(),
     };
 
@@ -31,10 +143,17 @@ pub fn run_compile(subcommand: SubCommands) -> Result<()> {
         None => std::env::var("BRACK_PLUGINS_PATH").unwrap_or_default(),
     };
 
+    // Without a plugins directory, only the built-in `brack` module is available.
+    let entries = match plugins_dir_path.as_str() {
+        "" => vec![],
+        path => std::fs::read_dir(path)
+            .map_err(|error| {
+                anyhow::anyhow!("Could not read the plugins directory {}: {}", path, error)
+            })?
+            .collect::<Result<Vec<_>, _>>()?,
+    };
     let pattern = Regex::new(r"(?<module_name>[[:alpha:]]+)_[[:alnum:]]+.wasm").unwrap();
-    let entries = std::fs::read_dir(plugins_dir_path)?;
     for entry in entries {
-        let entry = entry?;
         let path = entry.path();
         let capture = pattern.captures(
             path.to_str()
@@ -57,9 +176,20 @@ pub fn run_compile(subcommand: SubCommands) -> Result<()> {
         anyhow::bail!("Filename must end with .[]");
     }
 
+    let id_strategy = if random_ids {
+        IdStrategy::Random
+    } else {
+        IdStrategy::Deterministic
+    };
+
+    let mut source_map = SourceMap::new();
+    let file = source_map.load(&filename)?;
+    // Ids do not depend on how the path is spelled on the command line.
+    let id_path = id_path(&filename, std::env::current_dir()?);
+
     match output_level {
         1 => {
-            let tokens = brack_tokenizer::tokenize::tokenize(&filename)?;
+            let tokens = brack_tokenizer::tokenize::tokenize_file(&source_map, file)?;
             if json {
                 let json = serde_json::to_string(&tokens)?;
                 println!("{}", json);
@@ -70,8 +200,9 @@ pub fn run_compile(subcommand: SubCommands) -> Result<()> {
             }
         }
         2 => {
-            let tokens = brack_tokenizer::tokenize::tokenize(&filename)?;
-            let cst = brack_parser::parse::parse(&tokens)?;
+            let tokens = brack_tokenizer::tokenize::tokenize_file(&source_map, file)?;
+            let mut cst = parse(&tokens, &source_map)?;
+            brack_parser::id::assign_ids(&mut cst, &id_path, id_strategy);
             if json {
                 let json = serde_json::to_string(&cst)?;
                 println!("{}", json);
@@ -80,25 +211,27 @@ pub fn run_compile(subcommand: SubCommands) -> Result<()> {
             }
         }
         3 => {
-            let tokens = brack_tokenizer::tokenize::tokenize(&filename)?;
-            let cst = brack_parser::parse::parse(&tokens)?;
-            let (ast, _errors) = brack_transformer::transform::transform(&cst);
+            let mut ast = transform(file, &mut source_map)?;
+            assign_ids(&mut ast, &id_path, id_strategy);
             if json {
                 let json = serde_json::to_string(&ast)?;
                 println!("{}", json);
-            } else if !_errors.is_empty() {
-                for error in _errors {
-                    println!("{:?}", error);
-                }
             } else {
                 println!("{:?}", ast);
             }
         }
         4 => {
-            let tokens = brack_tokenizer::tokenize::tokenize(&filename)?;
-            let cst = brack_parser::parse::parse(&tokens)?;
-            let (ast, _errors) = brack_transformer::transform::transform(&cst);
-            let expanded_ast = brack_expander::expand::expander(&ast, &mut plugins)?;
+            let mut ast = transform(file, &mut source_map)?;
+            assign_ids(&mut ast, &id_path, id_strategy);
+            set_metadata(file, &source_map, &mut plugins)?;
+            let mut expanded_ast = expand(
+                &ast,
+                &mut plugins,
+                macro_depth,
+                trace_expansion,
+                &source_map,
+            )?;
+            assign_ids(&mut expanded_ast, &id_path, id_strategy);
             if json {
                 let json = serde_json::to_string(&expanded_ast)?;
                 println!("{}", json);
@@ -110,11 +243,19 @@ pub fn run_compile(subcommand: SubCommands) -> Result<()> {
             if json {
                 anyhow::bail!("Cannot output JSON at output level 5.")
             }
-            let tokens = brack_tokenizer::tokenize::tokenize(&filename)?;
-            let cst = brack_parser::parse::parse(&tokens)?;
-            let (ast, _errors) = brack_transformer::transform::transform(&cst);
-            let expanded_ast = brack_expander::expand::expander(&ast, &mut plugins)?;
-            let gen = brack_codegen::generate::generate(&expanded_ast, &mut plugins)?;
+            let mut ast = transform(file, &mut source_map)?;
+            assign_ids(&mut ast, &id_path, id_strategy);
+            set_metadata(file, &source_map, &mut plugins)?;
+            let mut expanded_ast = expand(
+                &ast,
+                &mut plugins,
+                macro_depth,
+                trace_expansion,
+                &source_map,
+            )?;
+            assign_ids(&mut expanded_ast, &id_path, id_strategy);
+            let gen = brack_codegen::generate::generate(&expanded_ast, &mut plugins)
+                .map_err(|error| render(error, &source_map))?;
             println!("{}", gen);
         }
         _ => anyhow::bail!("Invalid output level."),
@@ -151,7 +292,14 @@ out
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
+    if let Err(error) = run().await {
+        eprintln!("{:#}", error);
+        std::process::exit(1);
+    }
+}
+
+async fn run() -> Result<()> {
     let args = Args::parse();
     match args.subcommand {
         SubCommands::Build => {
@@ -167,6 +315,10 @@ async fn main() -> Result<()> {
         }
         SubCommands::New { name } => new_project(&name)?,
         SubCommands::Add { schema } => brack_project_manager::plugin::add_plugin(&schema).await?,
+        SubCommands::Fmt { check } => {
+            let project = brack_project_manager::project::Project::new(".");
+            project.fmt(check)?;
+        }
         SubCommands::Version => {
             let version = match std::env::var("APP_VERSION") {
                 Ok(version) => version,
@@ -1,4 +1,10 @@
-use clap::Subcommand;
+use clap::{Subcommand, ValueEnum};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TraceFormat {
+    Text,
+    Json,
+}
 
 #[derive(Debug, Subcommand)]
 pub enum SubCommands {
@@ -20,6 +26,18 @@ pub enum SubCommands {
         /// Output as JSON. This flag can not be used with output level 5.
         #[clap(long)]
         json: bool,
+
+        /// Assign random ids to nodes instead of ids derived from the file path and node positions.
+        #[clap(long)]
+        random_ids: bool,
+
+        /// The limit of macro expansions nested in the results of other macros.
+        #[clap(long, default_value_t = brack_expander::expand::DEFAULT_MAX_DEPTH)]
+        macro_depth: usize,
+
+        /// Print every macro and template expansion to stderr, as text or JSON.
+        #[clap(long, value_enum)]
+        trace_expansion: Option<TraceFormat>,
     },
     Build,
     LanguageServer,
@@ -31,4 +49,10 @@ pub enum SubCommands {
         schema: String,
     },
     Version,
+    /// Format the documents in the `docs` directory and its subdirectories.
+    Fmt {
+        /// Only check whether the documents are formatted, without rewriting them.
+        #[clap(long)]
+        check: bool,
+    },
 }
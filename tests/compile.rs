@@ -0,0 +1,27 @@
+use std::{path::PathBuf, process::Command};
+
+fn write_document(name: &str, text: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("brack-cli-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("a.[]");
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn test_compile_without_plugins() {
+    let path = write_document("without-plugins", "Built in [brack.date %Y].\n");
+    let output = Command::new(env!("CARGO_BIN_EXE_brack"))
+        .args(["compile", "-b", "html", "-f"])
+        .arg(&path)
+        .env_remove("BRACK_PLUGINS_PATH")
+        .env("SOURCE_DATE_EPOCH", "1728000000")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Built in 2024.\n");
+}